@@ -0,0 +1,86 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use image::imageops::{self, FilterType};
+use mimesis::{BinaryImage, Connectivity, TraceOptions};
+
+/// Mask of the sample image shipped with the binary
+const GIRL_MASK: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/bin/assets/girl_mask.png");
+
+/// Mask where `foreground` decides every pixel.
+fn synthetic(width: u32, height: u32, foreground: impl Fn(u32, u32) -> bool) -> BinaryImage {
+    let pixels: Vec<u8> = (0..width * height)
+        .map(|i| u8::from(foreground(i % width, i / width)))
+        .collect();
+    BinaryImage::from_raw(width, height, &pixels)
+}
+
+/// Disk covering most of the image, a single long ring over mostly uniform rows.
+fn disk(width: u32, height: u32) -> BinaryImage {
+    let (cx, cy) = (width as i64 / 2, height as i64 / 2);
+    let radius = cx.min(cy) * 9 / 10;
+    synthetic(width, height, |x, y| {
+        let (dx, dy) = (x as i64 - cx, y as i64 - cy);
+        dx * dx + dy * dy < radius * radius
+    })
+}
+
+/// Pseudo-random pixels, the worst case with a ring every few pixels.
+fn noise(width: u32, height: u32, density: u32) -> BinaryImage {
+    synthetic(width, height, |x, y| {
+        let mut hash = (y as u64) << 32 | x as u64;
+        hash = hash.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        hash ^= hash >> 29;
+        hash = hash.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        hash ^= hash >> 32;
+        (hash % 100) < density as u64
+    })
+}
+
+/// Grid of square blobs with a square hole each, many mid-sized rings with a hierarchy.
+fn blobs(width: u32, height: u32) -> BinaryImage {
+    synthetic(width, height, |x, y| {
+        let (u, v) = (x % 64, y % 64);
+        (8..56).contains(&u) && (8..56).contains(&v) && !((24..40).contains(&u) && (24..40).contains(&v))
+    })
+}
+
+/// Real mask scaled by `scale`, keeping its hard edges.
+fn girl(scale: u32) -> BinaryImage {
+    let mask = image::open(GIRL_MASK).expect("sample mask").to_luma8();
+    let (width, height) = mask.dimensions();
+    let mask = imageops::resize(&mask, width * scale, height * scale, FilterType::Nearest);
+    BinaryImage::from_mask(mask)
+}
+
+fn bench_masks(c: &mut Criterion, group: &str, masks: Vec<(String, BinaryImage)>) {
+    let mut group = c.benchmark_group(group);
+    group.sample_size(10);
+    for (name, mask) in &masks {
+        group.throughput(Throughput::Elements(mask.width() as u64 * mask.height() as u64));
+        for connectivity in [Connectivity::Four, Connectivity::Eight] {
+            let options = TraceOptions { connectivity, ..Default::default() };
+            let id = BenchmarkId::new(format!("{connectivity:?}"), name);
+            group.bench_with_input(id, mask, |b, mask| b.iter(|| mask.trace_polygons_with(&options)));
+        }
+    }
+    group.finish();
+}
+
+fn synthetic_masks(c: &mut Criterion) {
+    bench_masks(c, "trace_synthetic", vec![
+        ("disk_1k".to_string(), disk(1024, 1024)),
+        ("disk_8k".to_string(), disk(7680, 4320)),
+        ("blobs_4k".to_string(), blobs(3840, 2160)),
+        ("noise_1k".to_string(), noise(1024, 1024, 50)),
+        ("sparse_noise_4k".to_string(), noise(3840, 2160, 1)),
+    ]);
+}
+
+fn real_masks(c: &mut Criterion) {
+    bench_masks(c, "trace_real", vec![
+        ("girl".to_string(), girl(1)),
+        ("girl_x4".to_string(), girl(4)),
+    ]);
+}
+
+criterion_group!(benches, synthetic_masks, real_masks);
+criterion_main!(benches);
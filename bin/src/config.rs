@@ -5,11 +5,12 @@ use std::path::{Path, PathBuf};
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, ValueEnum, Debug, Serialize, Deserialize)]
+#[derive(Clone, ValueEnum, Debug, Default, Serialize, Deserialize)]
 pub(crate) enum MaskMethod {
     /// Use luminance/brightness to generate mask
     Luminance,
     /// Use alpha channel to generate mask
+    #[default]
     Alpha,
     /// Use red channel to generate mask
     Red,
@@ -17,12 +18,139 @@ pub(crate) enum MaskMethod {
     Green,
     /// Use blue channel to generate mask
     Blue,
+    /// Key out the pixels close to the key color
+    ChromaKey,
+    /// Key out the pixels close to the color of the image corners
+    BorderColor,
+    /// Compare the luminance to the mean of its neighbourhood
+    AdaptiveMean,
+    /// Compare the luminance to the Gaussian weighted mean of its neighbourhood
+    AdaptiveGaussian,
 }
 
-impl Default for MaskMethod {
-    fn default() -> Self {
-        MaskMethod::Alpha
-    }
+#[derive(Clone, Copy, ValueEnum, Debug, Default, Serialize, Deserialize)]
+pub(crate) enum KeySpace {
+    /// CIE L*a*b*, the tolerance is a ΔE color difference
+    #[default]
+    Lab,
+    /// HSV cone, more tolerant of uneven lighting of the backdrop
+    Hsv,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, Default, Serialize, Deserialize)]
+pub(crate) enum ThresholdMode {
+    /// Use the configured threshold
+    #[default]
+    Fixed,
+    /// Otsu's method, for images with distinct foreground and background
+    Otsu,
+    /// Triangle method, for small objects on a uniform background
+    Triangle,
+    /// Percentile of the histogram
+    Percentile,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, Default, Serialize, Deserialize)]
+pub(crate) enum Tracer {
+    /// Follow the pixel corners of the mask
+    #[default]
+    Corners,
+    /// Marching squares on the mask channel, with sub-pixel vertices
+    MarchingSquares,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, Default, Serialize, Deserialize)]
+pub(crate) enum TraceConnectivity {
+    /// Pixels touching diagonally belong to different polygons
+    #[default]
+    Four,
+    /// Pixels touching diagonally belong to the same polygon
+    Eight,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, Default, Serialize, Deserialize)]
+pub(crate) enum SimplifyMode {
+    /// Tolerance in pixels
+    #[default]
+    Absolute,
+    /// Tolerance relative to the bounding box diagonal of each polygon
+    Relative,
+    /// Vertex budget of each polygon
+    PolygonBudget,
+    /// Vertex budget shared by all the polygons of the image
+    ImageBudget,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, Default, Serialize, Deserialize)]
+pub(crate) enum Triangulation {
+    /// Ear clipping, fast but prone to long sliver triangles
+    #[default]
+    Earcut,
+    /// Constrained Delaunay triangulation refined with Steiner points
+    Delaunay,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, Default, Serialize, Deserialize)]
+pub(crate) enum SmoothMethod {
+    /// Chaikin corner cutting, rounding every corner
+    #[default]
+    Chaikin,
+    /// Catmull-Rom spline through the vertices between the corners
+    CatmullRom,
+    /// B-spline approaching the vertices between the corners
+    BSpline,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, Default, Serialize, Deserialize)]
+pub(crate) enum MaskElement {
+    /// Square neighbourhood
+    Square,
+    /// Horizontal and vertical neighbours only
+    Cross,
+    /// Round neighbourhood
+    #[default]
+    Disk,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, Default, Serialize, Deserialize)]
+pub(crate) enum OutputFormat {
+    /// Wavefront OBJ with an MTL material file
+    #[default]
+    Obj,
+    /// glTF 2.0 with an external binary buffer and textures
+    Gltf,
+    /// Binary glTF 2.0 with embedded textures
+    Glb,
+    /// Binary STL for 3D printing
+    Stl,
+    /// ASCII STL for 3D printing
+    StlAscii,
+    /// Binary little-endian Stanford PLY
+    Ply,
+    /// ASCII Stanford PLY
+    PlyAscii,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, Default, Serialize, Deserialize)]
+pub(crate) enum EdgeProfile {
+    /// Straight side walls
+    #[default]
+    Flat,
+    /// Straight cut on the front and back edges
+    Chamfer,
+    /// Rounded front and back edges
+    Round,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, Default, Serialize, Deserialize)]
+pub(crate) enum InflateProfile {
+    /// Rounded, balloon-like cross-section
+    #[default]
+    Circular,
+    /// Softer cross-section near the contour
+    Parabolic,
+    /// Straight slopes up to a ridge
+    Linear,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +171,9 @@ pub(crate) struct InputConfig {
     pub input: PathBuf,
     /// Optional binary mask image path
     pub mask: Option<PathBuf>,
+    /// Optional grayscale depth map path for relief meshes
+    #[serde(default)]
+    pub depth_map: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,21 +189,143 @@ pub(crate) struct ProcessingConfig {
     /// Simplification tolerance for Ramer-Douglas-Peucker algorithm
     #[serde(default)]
     pub simplify_tolerance: f64,
+    /// How the simplification tolerance is chosen
+    #[serde(default)]
+    pub simplify_mode: SimplifyMode,
+    /// Simplification tolerance as a fraction of the bounding box diagonal of each polygon,
+    /// with the relative simplify mode
+    #[serde(default = "default_relative_tolerance")]
+    pub relative_tolerance: f64,
+    /// Number of vertices kept by the simplification, with the budget simplify modes. The
+    /// smoothing runs afterwards and may add vertices past it.
+    #[serde(default = "default_vertex_budget")]
+    pub vertex_budget: usize,
+    /// Keep the simplified polygons valid, refining the spans where rings cross and dropping
+    /// the rings that degenerate
+    #[serde(default = "default_preserve_topology")]
+    pub preserve_topology: bool,
     /// Number of Chaikin smoothing iterations
     #[serde(default)]
     pub smooth_iterations: usize,
+    /// Curve smoothing the polygons
+    #[serde(default)]
+    pub smooth_method: SmoothMethod,
+    /// Angle in degrees above which vertices are corners kept sharp by the spline smooth methods
+    /// and the Bézier curves of the SVG file
+    #[serde(default = "default_corner_angle")]
+    pub corner_angle: f64,
+    /// Length of the segments of the spline smoothed polygons (in pixels)
+    #[serde(default = "default_smooth_segment_length")]
+    pub smooth_segment_length: f64,
+    /// Algorithm triangulating the front and back faces
+    #[serde(default)]
+    pub triangulation: Triangulation,
+    /// Smallest angle in degrees of the triangles of a Delaunay triangulation, 0 disables
+    /// angle refinement
+    #[serde(default = "default_min_angle")]
+    pub min_angle: f64,
+    /// Largest area of the triangles of a Delaunay triangulation (in square pixels), 0 leaves
+    /// it unbounded
+    #[serde(default)]
+    pub max_triangle_area: f64,
+    /// Largest number of Steiner points inserted by a Delaunay triangulation, defaults to ten
+    /// times the number of vertices of the polygon
+    #[serde(default)]
+    pub max_steiner_points: Option<usize>,
     /// Extrusion height for 3D mesh
     #[serde(default)]
     pub extrude_height: f64,
+    /// Shape of the front and back edges of the extruded mesh
+    #[serde(default)]
+    pub edge_profile: EdgeProfile,
+    /// Width of the chamfer or rounded edges (in pixels)
+    #[serde(default)]
+    pub bevel_width: f64,
+    /// Number of segments of rounded edges
+    #[serde(default = "default_bevel_segments")]
+    pub bevel_segments: usize,
+    /// Height added on each face by inflation, 0 disables inflation
+    #[serde(default)]
+    pub inflate_height: f64,
+    /// Cross-section of inflated meshes
+    #[serde(default)]
+    pub inflate_profile: InflateProfile,
+    /// Height of the relief raised on the front face, 0 disables relief
+    #[serde(default)]
+    pub relief_scale: f64,
+    /// Spacing of the interior vertices of inflated and relief meshes (in pixels)
+    #[serde(default = "default_interior_spacing", alias = "inflate_spacing")]
+    pub interior_spacing: f64,
+    /// Angle in degrees below which side wall normals are smoothed
+    #[serde(default = "default_crease_angle")]
+    pub crease_angle: f64,
+    /// Method tracing the polygons of the mask
+    #[serde(default)]
+    pub tracer: Tracer,
+    /// Connectivity of the mask pixels when tracing polygons
+    #[serde(default)]
+    pub connectivity: TraceConnectivity,
     /// Minimum polygon dimension (in pixels)
     #[serde(default)]
     pub min_polygon_dimension: usize,
+    /// Minimum hole dimension (in pixels), defaults to the minimum polygon dimension
+    #[serde(default)]
+    pub min_hole_dimension: Option<usize>,
+    /// Minimum number of pixels of the holes traced, smaller holes are filled
+    #[serde(default)]
+    pub min_hole_area: usize,
+    /// Fill the holes of the mask before tracing
+    #[serde(default)]
+    pub fill_holes: bool,
+    /// Maximum number of pixels of the holes filled by `fill_holes`, 0 fills every hole
+    #[serde(default)]
+    pub fill_holes_max_area: usize,
+    /// Minimum number of pixels of the mask components traced, 0 keeps every component
+    #[serde(default)]
+    pub min_component_area: usize,
+    /// Number of largest mask components kept, 0 keeps every component
+    #[serde(default)]
+    pub max_components: usize,
+    /// Size of a pixel in millimetres for STL export
+    #[serde(default = "default_mm_per_pixel")]
+    pub mm_per_pixel: f64,
     /// Threshold for binary mask generation (0-255)
     #[serde(default)]
     pub threshold: u8,
+    /// Method choosing the threshold for binary mask generation
+    #[serde(default)]
+    pub threshold_method: ThresholdMode,
+    /// Percentage of the pixels below the threshold with the percentile method (0-100)
+    #[serde(default = "default_threshold_percentile")]
+    pub threshold_percentile: f64,
     /// Method for generating binary mask from texture
     #[serde(default)]
     pub mask_method: MaskMethod,
+    /// Key color of the chroma key mask method, as a hex code
+    #[serde(default = "default_key_color")]
+    pub key_color: String,
+    /// Distance to the key color under which pixels are background, with the chroma key and
+    /// border color mask methods
+    #[serde(default = "default_key_tolerance")]
+    pub key_tolerance: f64,
+    /// Color space in which the distance to the key color is measured
+    #[serde(default)]
+    pub key_space: KeySpace,
+    /// Size in pixels of the neighbourhood of the adaptive mask methods
+    #[serde(default = "default_adaptive_window")]
+    pub adaptive_window: u32,
+    /// Value subtracted from the local mean of the adaptive mask methods
+    #[serde(default = "default_adaptive_offset")]
+    pub adaptive_offset: f64,
+    /// Radius of the opening removing specks from the mask, 0 disables it
+    #[serde(default)]
+    pub mask_open_radius: u32,
+    /// Radius of the closing filling pinholes in the mask, 0 disables it
+    #[serde(default)]
+    pub mask_close_radius: u32,
+    /// Structuring element of the mask cleanup
+    #[serde(default)]
+    pub mask_element: MaskElement,
     /// Enable verbose output
     #[serde(default)]
     pub verbose: bool,
@@ -102,6 +355,9 @@ pub(crate) struct OutputConfig {
     /// Output folder for processed files
     #[serde(default)]
     pub output_folder: PathBuf,
+    /// 3D mesh file format
+    #[serde(default)]
+    pub format: OutputFormat,
     /// Side texture path for OBJ export
     #[serde(default)]
     pub side_texture: Option<PathBuf>,
@@ -111,6 +367,86 @@ pub(crate) struct OutputConfig {
     /// Skip saving intermediate polygon images
     #[serde(default)]
     pub skip_intermediates: bool,
+    /// Add per-vertex colors sampled from the texture to PLY exports
+    #[serde(default)]
+    pub ply_vertex_colors: bool,
+    /// Spread in pixels of the signed distance field saved next to the mask, 0 disables it
+    #[serde(default)]
+    pub sdf_spread: f32,
+    /// Save the signed distance field as a 16-bit image instead of an 8-bit one
+    #[serde(default)]
+    pub sdf_16bit: bool,
+    /// Save the polygons as an SVG file of cubic Bézier curves
+    #[serde(default)]
+    pub svg: bool,
+    /// Maximum distance in pixels between the polygons and the Bézier curves of the SVG file,
+    /// 0 writes the polygons as they are
+    #[serde(default = "default_svg_fit_error")]
+    pub svg_fit_error: f64,
+}
+
+fn default_bevel_segments() -> usize {
+    4
+}
+
+fn default_interior_spacing() -> f64 {
+    8.0
+}
+
+fn default_crease_angle() -> f64 {
+    30.0
+}
+
+fn default_threshold_percentile() -> f64 {
+    50.0
+}
+
+fn default_key_color() -> String {
+    "#00b140".to_string()
+}
+
+fn default_key_tolerance() -> f64 {
+    20.0
+}
+
+fn default_adaptive_window() -> u32 {
+    31
+}
+
+fn default_adaptive_offset() -> f64 {
+    5.0
+}
+
+fn default_relative_tolerance() -> f64 {
+    0.005
+}
+
+fn default_vertex_budget() -> usize {
+    1000
+}
+
+fn default_preserve_topology() -> bool {
+    true
+}
+
+fn default_corner_angle() -> f64 {
+    60.0
+}
+
+fn default_smooth_segment_length() -> f64 {
+    4.0
+}
+
+fn default_svg_fit_error() -> f64 {
+    1.0
+}
+
+fn default_min_angle() -> f64 {
+    20.0
+}
+
+fn default_mm_per_pixel() -> f64 {
+    1.0
 }
 
 impl Default for Config {
@@ -119,6 +455,7 @@ impl Default for Config {
             input: InputConfig {
                 input: PathBuf::from("texture.png"),
                 mask: None,
+                depth_map: None,
             },
             processing: ProcessingConfig {
                 #[cfg(feature = "background-remover")]
@@ -126,11 +463,49 @@ impl Default for Config {
                 #[cfg(feature = "background-remover")]
                 onnx_model_path: None,
                 simplify_tolerance: 10.0,
+                simplify_mode: SimplifyMode::Absolute,
+                relative_tolerance: default_relative_tolerance(),
+                vertex_budget: default_vertex_budget(),
+                preserve_topology: true,
                 smooth_iterations: 1,
+                smooth_method: SmoothMethod::Chaikin,
+                corner_angle: default_corner_angle(),
+                smooth_segment_length: default_smooth_segment_length(),
+                triangulation: Triangulation::Earcut,
+                min_angle: default_min_angle(),
+                max_triangle_area: 0.0,
+                max_steiner_points: None,
                 extrude_height: 20.0,
+                edge_profile: EdgeProfile::Flat,
+                bevel_width: 0.0,
+                bevel_segments: default_bevel_segments(),
+                inflate_height: 0.0,
+                inflate_profile: InflateProfile::Circular,
+                relief_scale: 0.0,
+                interior_spacing: default_interior_spacing(),
+                crease_angle: default_crease_angle(),
+                tracer: Tracer::Corners,
+                connectivity: TraceConnectivity::Four,
                 min_polygon_dimension: 0,
+                min_hole_dimension: None,
+                min_hole_area: 0,
+                fill_holes: false,
+                fill_holes_max_area: 0,
+                min_component_area: 0,
+                max_components: 0,
+                mm_per_pixel: default_mm_per_pixel(),
                 threshold: 128,
+                threshold_method: ThresholdMode::Fixed,
+                threshold_percentile: default_threshold_percentile(),
                 mask_method: MaskMethod::Alpha,
+                key_color: default_key_color(),
+                key_tolerance: default_key_tolerance(),
+                key_space: KeySpace::Lab,
+                adaptive_window: default_adaptive_window(),
+                adaptive_offset: default_adaptive_offset(),
+                mask_open_radius: 0,
+                mask_close_radius: 0,
+                mask_element: MaskElement::Disk,
                 verbose: false,
                 benchmark: false
             },
@@ -149,9 +524,15 @@ impl Default for Config {
             },
             output: OutputConfig {
                 output_folder: PathBuf::from("output"),
+                format: OutputFormat::Obj,
                 side_texture: None,
                 back_texture: None,
                 skip_intermediates: false,
+                ply_vertex_colors: false,
+                sdf_spread: 0.0,
+                sdf_16bit: false,
+                svg: false,
+                svg_fit_error: default_svg_fit_error(),
             },
         }
     }
@@ -8,7 +8,7 @@ use std::sync::{Arc, Mutex};
 use clap::Parser;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use rayon::ThreadPoolBuilder;
-use crate::config::{Config, MaskMethod};
+use crate::config::{Config, EdgeProfile, InflateProfile, KeySpace, MaskElement, MaskMethod, OutputFormat, SimplifyMode, SmoothMethod, ThresholdMode, TraceConnectivity, Tracer, Triangulation};
 use crate::processing::Processor;
 use crate::stats::{ProcessingStats};
 
@@ -33,10 +33,18 @@ struct Args {
     #[arg(short, long)]
     mask: Option<PathBuf>,
 
+    /// Optional grayscale depth map for relief meshes (if not provided, the texture luminance is used)
+    #[arg(long)]
+    depth_map: Option<PathBuf>,
+
     /// Output directory
     #[arg(short, long)]
     output: Option<PathBuf>,
 
+    /// 3D mesh output format
+    #[arg(long)]
+    format: Option<OutputFormat>,
+
     /// Configuration file path
     #[arg(short, long)]
     config: Option<PathBuf>,
@@ -65,26 +73,179 @@ struct Args {
     #[arg(long)]
     simplify_tolerance: Option<f64>,
 
+    /// How the simplification tolerance is chosen
+    #[arg(long)]
+    simplify_mode: Option<SimplifyMode>,
+
+    /// Simplification tolerance as a fraction of the bounding box diagonal of each polygon
+    #[arg(long)]
+    relative_tolerance: Option<f64>,
+
+    /// Number of vertices kept by the simplification, per polygon or per image, before smoothing
+    #[arg(long)]
+    vertex_budget: Option<usize>,
+
+    /// Keep the simplified polygons valid, dropping the rings that degenerate
+    #[arg(long)]
+    preserve_topology: Option<bool>,
+
     /// Number of Chaikin smoothing iterations
     #[arg(long)]
     smooth_iterations: Option<usize>,
 
+    /// Curve smoothing the polygons
+    #[arg(long)]
+    smooth_method: Option<SmoothMethod>,
+
+    /// Angle in degrees above which vertices are corners kept sharp by the spline smooth methods
+    /// and the Bézier curves of the SVG file
+    #[arg(long)]
+    corner_angle: Option<f64>,
+
+    /// Length of the segments of the spline smoothed polygons (in pixels)
+    #[arg(long)]
+    smooth_segment_length: Option<f64>,
+
+    /// Algorithm triangulating the front and back faces
+    #[arg(long)]
+    triangulation: Option<Triangulation>,
+
+    /// Smallest angle in degrees of the triangles of a Delaunay triangulation, 0 disables angle refinement
+    #[arg(long)]
+    min_angle: Option<f64>,
+
+    /// Largest area of the triangles of a Delaunay triangulation (in square pixels), 0 leaves it unbounded
+    #[arg(long)]
+    max_triangle_area: Option<f64>,
+
+    /// Largest number of Steiner points inserted by a Delaunay triangulation
+    #[arg(long)]
+    max_steiner_points: Option<usize>,
+
     /// Extrusion height for 3D mesh
     #[arg(long)]
     extrude_height: Option<f64>,
 
+    /// Shape of the front and back edges of the extruded mesh
+    #[arg(long)]
+    edge_profile: Option<EdgeProfile>,
+
+    /// Width of the chamfer or rounded edges (in pixels)
+    #[arg(long)]
+    bevel_width: Option<f64>,
+
+    /// Number of segments of rounded edges
+    #[arg(long)]
+    bevel_segments: Option<usize>,
+
+    /// Height added on each face by inflation, 0 disables inflation
+    #[arg(long)]
+    inflate_height: Option<f64>,
+
+    /// Cross-section of inflated meshes
+    #[arg(long)]
+    inflate_profile: Option<InflateProfile>,
+
+    /// Height of the relief raised on the front face, 0 disables relief
+    #[arg(long)]
+    relief_scale: Option<f64>,
+
+    /// Spacing of the interior vertices of inflated and relief meshes (in pixels)
+    #[arg(long, alias = "inflate-spacing")]
+    interior_spacing: Option<f64>,
+
+    /// Angle in degrees below which side wall normals are smoothed
+    #[arg(long)]
+    crease_angle: Option<f64>,
+
+    /// Method tracing the polygons of the mask
+    #[arg(long)]
+    tracer: Option<Tracer>,
+
+    /// Connectivity of the mask pixels when tracing polygons
+    #[arg(long)]
+    connectivity: Option<TraceConnectivity>,
+
     /// Minimum polygon dimension (in pixels)
     #[arg(long)]
     min_polygon_dimension: Option<usize>,
 
+    /// Minimum hole dimension (in pixels), defaults to the minimum polygon dimension
+    #[arg(long)]
+    min_hole_dimension: Option<usize>,
+
+    /// Minimum number of pixels of the holes traced, smaller holes are filled
+    #[arg(long)]
+    min_hole_area: Option<usize>,
+
+    /// Fill the holes of the mask before tracing
+    #[arg(long)]
+    fill_holes: Option<bool>,
+
+    /// Maximum number of pixels of the holes filled by --fill-holes, 0 fills every hole
+    #[arg(long)]
+    fill_holes_max_area: Option<usize>,
+
+    /// Minimum number of pixels of the mask components traced, 0 keeps every component
+    #[arg(long)]
+    min_component_area: Option<usize>,
+
+    /// Number of largest mask components kept, 0 keeps every component
+    #[arg(long)]
+    max_components: Option<usize>,
+
+    /// Size of a pixel in millimetres for STL export
+    #[arg(long)]
+    mm_per_pixel: Option<f64>,
+
     /// Threshold for binary mask generation (0-255)
     #[arg(long)]
     threshold: Option<u8>,
 
+    /// Method choosing the threshold for binary mask generation
+    #[arg(long)]
+    threshold_method: Option<ThresholdMode>,
+
+    /// Percentage of the pixels below the threshold with the percentile method (0-100)
+    #[arg(long)]
+    threshold_percentile: Option<f64>,
+
     /// Method for generating binary mask from texture
     #[arg(long)]
     mask_method: Option<MaskMethod>,
 
+    /// Key color of the chroma key mask method, as a hex code (e.g. #00b140)
+    #[arg(long)]
+    key_color: Option<String>,
+
+    /// Distance to the key color under which pixels are background
+    #[arg(long)]
+    key_tolerance: Option<f64>,
+
+    /// Color space in which the distance to the key color is measured
+    #[arg(long)]
+    key_space: Option<KeySpace>,
+
+    /// Size in pixels of the neighbourhood of the adaptive mask methods
+    #[arg(long)]
+    adaptive_window: Option<u32>,
+
+    /// Value subtracted from the local mean of the adaptive mask methods
+    #[arg(long)]
+    adaptive_offset: Option<f64>,
+
+    /// Radius of the opening removing specks from the mask, 0 disables it
+    #[arg(long)]
+    mask_open_radius: Option<u32>,
+
+    /// Radius of the closing filling pinholes in the mask, 0 disables it
+    #[arg(long)]
+    mask_close_radius: Option<u32>,
+
+    /// Structuring element of the mask cleanup
+    #[arg(long)]
+    mask_element: Option<MaskElement>,
+
     /// Side texture file name for OBJ export
     #[arg(long)]
     side_texture: Option<PathBuf>,
@@ -97,6 +258,27 @@ struct Args {
     #[arg(long)]
     skip_intermediates: Option<bool>,
 
+    /// Add per-vertex colors sampled from the texture to PLY exports
+    #[arg(long)]
+    ply_vertex_colors: Option<bool>,
+
+    /// Spread in pixels of the signed distance field saved next to the mask, 0 disables it
+    #[arg(long)]
+    sdf_spread: Option<f32>,
+
+    /// Save the signed distance field as a 16-bit image instead of an 8-bit one
+    #[arg(long)]
+    sdf_16bit: Option<bool>,
+
+    /// Save the polygons as an SVG file of cubic Bézier curves
+    #[arg(long)]
+    svg: Option<bool>,
+
+    /// Maximum distance in pixels between the polygons and the Bézier curves of the SVG file,
+    /// 0 writes the polygons as they are
+    #[arg(long)]
+    svg_fit_error: Option<f64>,
+
     /// Verbose output
     #[arg(long)]
     verbose: bool,
@@ -181,6 +363,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     if args.mask.is_some() {
         config.input.mask = args.mask;
     }
+    if args.depth_map.is_some() {
+        config.input.depth_map = args.depth_map;
+    }
     #[cfg(feature = "background-remover")]
     if let Some(onnx_background_removal) = args.onnx_background_removal {
         config.processing.use_onnx_background_removal = onnx_background_removal;
@@ -192,15 +377,135 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     if let Some(simplify_tolerance) = args.simplify_tolerance {
         config.processing.simplify_tolerance = simplify_tolerance;
     }
+    if let Some(simplify_mode) = args.simplify_mode {
+        config.processing.simplify_mode = simplify_mode;
+    }
+    if let Some(relative_tolerance) = args.relative_tolerance {
+        config.processing.relative_tolerance = relative_tolerance;
+    }
+    if let Some(vertex_budget) = args.vertex_budget {
+        config.processing.vertex_budget = vertex_budget;
+    }
+    if let Some(preserve_topology) = args.preserve_topology {
+        config.processing.preserve_topology = preserve_topology;
+    }
     if let Some(smooth_iterations) = args.smooth_iterations {
         config.processing.smooth_iterations = smooth_iterations;
     }
+    if let Some(smooth_method) = args.smooth_method {
+        config.processing.smooth_method = smooth_method;
+    }
+    if let Some(corner_angle) = args.corner_angle {
+        config.processing.corner_angle = corner_angle;
+    }
+    if let Some(smooth_segment_length) = args.smooth_segment_length {
+        config.processing.smooth_segment_length = smooth_segment_length;
+    }
+    if let Some(triangulation) = args.triangulation {
+        config.processing.triangulation = triangulation;
+    }
+    if let Some(min_angle) = args.min_angle {
+        config.processing.min_angle = min_angle;
+    }
+    if let Some(max_triangle_area) = args.max_triangle_area {
+        config.processing.max_triangle_area = max_triangle_area;
+    }
+    if args.max_steiner_points.is_some() {
+        config.processing.max_steiner_points = args.max_steiner_points;
+    }
     if let Some(extrude_height) = args.extrude_height {
         config.processing.extrude_height = extrude_height;
     }
+    if let Some(edge_profile) = args.edge_profile {
+        config.processing.edge_profile = edge_profile;
+    }
+    if let Some(bevel_width) = args.bevel_width {
+        config.processing.bevel_width = bevel_width;
+    }
+    if let Some(bevel_segments) = args.bevel_segments {
+        config.processing.bevel_segments = bevel_segments;
+    }
+    if let Some(inflate_height) = args.inflate_height {
+        config.processing.inflate_height = inflate_height;
+    }
+    if let Some(inflate_profile) = args.inflate_profile {
+        config.processing.inflate_profile = inflate_profile;
+    }
+    if let Some(relief_scale) = args.relief_scale {
+        config.processing.relief_scale = relief_scale;
+    }
+    if let Some(interior_spacing) = args.interior_spacing {
+        config.processing.interior_spacing = interior_spacing;
+    }
+    if let Some(tracer) = args.tracer {
+        config.processing.tracer = tracer;
+    }
+    if let Some(connectivity) = args.connectivity {
+        config.processing.connectivity = connectivity;
+    }
+    if let Some(min_polygon_dimension) = args.min_polygon_dimension {
+        config.processing.min_polygon_dimension = min_polygon_dimension;
+    }
+    if args.min_hole_dimension.is_some() {
+        config.processing.min_hole_dimension = args.min_hole_dimension;
+    }
+    if let Some(min_hole_area) = args.min_hole_area {
+        config.processing.min_hole_area = min_hole_area;
+    }
+    if let Some(fill_holes) = args.fill_holes {
+        config.processing.fill_holes = fill_holes;
+    }
+    if let Some(fill_holes_max_area) = args.fill_holes_max_area {
+        config.processing.fill_holes_max_area = fill_holes_max_area;
+    }
+    if let Some(min_component_area) = args.min_component_area {
+        config.processing.min_component_area = min_component_area;
+    }
+    if let Some(max_components) = args.max_components {
+        config.processing.max_components = max_components;
+    }
+    if let Some(crease_angle) = args.crease_angle {
+        config.processing.crease_angle = crease_angle;
+    }
+    if let Some(mm_per_pixel) = args.mm_per_pixel {
+        config.processing.mm_per_pixel = mm_per_pixel;
+    }
     if let Some(threshold) = args.threshold {
         config.processing.threshold = threshold;
     }
+    if let Some(threshold_method) = args.threshold_method {
+        config.processing.threshold_method = threshold_method;
+    }
+    if let Some(threshold_percentile) = args.threshold_percentile {
+        config.processing.threshold_percentile = threshold_percentile;
+    }
+    if let Some(mask_method) = args.mask_method {
+        config.processing.mask_method = mask_method;
+    }
+    if let Some(key_color) = args.key_color {
+        config.processing.key_color = key_color;
+    }
+    if let Some(key_tolerance) = args.key_tolerance {
+        config.processing.key_tolerance = key_tolerance;
+    }
+    if let Some(key_space) = args.key_space {
+        config.processing.key_space = key_space;
+    }
+    if let Some(adaptive_window) = args.adaptive_window {
+        config.processing.adaptive_window = adaptive_window;
+    }
+    if let Some(adaptive_offset) = args.adaptive_offset {
+        config.processing.adaptive_offset = adaptive_offset;
+    }
+    if let Some(mask_open_radius) = args.mask_open_radius {
+        config.processing.mask_open_radius = mask_open_radius;
+    }
+    if let Some(mask_close_radius) = args.mask_close_radius {
+        config.processing.mask_close_radius = mask_close_radius;
+    }
+    if let Some(mask_element) = args.mask_element {
+        config.processing.mask_element = mask_element;
+    }
     if args.verbose {
         config.processing.verbose = true;
     }
@@ -213,12 +518,30 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     if let Some(output) = args.output {
         config.output.output_folder = output;
     }
+    if let Some(format) = args.format {
+        config.output.format = format;
+    }
     if let Some(continue_on_error) = args.continue_on_error {
         config.batch.continue_on_error = continue_on_error;
     }
     if let Some(skip_intermediates) = args.skip_intermediates {
         config.output.skip_intermediates = skip_intermediates;
     }
+    if let Some(ply_vertex_colors) = args.ply_vertex_colors {
+        config.output.ply_vertex_colors = ply_vertex_colors;
+    }
+    if let Some(sdf_spread) = args.sdf_spread {
+        config.output.sdf_spread = sdf_spread;
+    }
+    if let Some(sdf_16bit) = args.sdf_16bit {
+        config.output.sdf_16bit = sdf_16bit;
+    }
+    if let Some(svg) = args.svg {
+        config.output.svg = svg;
+    }
+    if let Some(svg_fit_error) = args.svg_fit_error {
+        config.output.svg_fit_error = svg_fit_error;
+    }
 
     // Parse include patterns from command line
     if let Some(include_patterns) = args.include_patterns {
@@ -237,6 +560,24 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     config.batch.exclude_patterns.push("*_mask*".to_string());
+    if config.processing.relief_scale > 0.0 && config.input.depth_map.is_none() {
+        config.batch.exclude_patterns.push("*_depth*".to_string());
+    }
+
+    // Traced contours only turn by right angles, which are all corners until simplified
+    let processing = &config.processing;
+    if !matches!(processing.smooth_method, SmoothMethod::Chaikin) && matches!(processing.tracer, Tracer::Corners) &&
+        matches!(processing.simplify_mode, SimplifyMode::Absolute | SimplifyMode::Relative) && processing.simplify_tolerance <= 0.0 {
+        eprintln!("Warning: without simplification every traced vertex is a corner, the {:?} smoothing leaves the contours unchanged",
+                  processing.smooth_method);
+    }
+
+    // The budget bounds the simplified contours, the smoothing then adds vertices to them
+    let smoothing = !matches!(processing.smooth_method, SmoothMethod::Chaikin) || processing.smooth_iterations > 0;
+    if matches!(processing.simplify_mode, SimplifyMode::PolygonBudget | SimplifyMode::ImageBudget) && smoothing {
+        eprintln!("Warning: the {:?} smoothing runs after the simplification and adds vertices past the vertex budget of {}",
+                  processing.smooth_method, processing.vertex_budget);
+    }
 
     // Find input files
     let input_files = find_input_files(&config.input.input, &config.batch.include_patterns, &config.batch.exclude_patterns)?;
@@ -321,16 +662,21 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         input_files
             .par_iter()
             .try_for_each(|input_file| -> anyhow::Result<()> {
-                let mask = if batch {
+                let (mask, depth_map) = if batch {
                     let stem = input_file.file_stem().and_then(|s| s.to_str()).unwrap_or("");
                     let mask_filename = format!("{}_mask.png", stem);
                     let mask_path = input_file.with_file_name(mask_filename);
-                    if !mask_path.exists() { None } else { Some(mask_path) }
+                    let depth_map_filename = format!("{}_depth.png", stem);
+                    let depth_map_path = input_file.with_file_name(depth_map_filename);
+                    (
+                        if !mask_path.exists() { None } else { Some(mask_path) },
+                        if !depth_map_path.exists() { None } else { Some(depth_map_path) },
+                    )
                 } else {
-                    config.input.mask.clone()
+                    (config.input.mask.clone(), config.input.depth_map.clone())
                 };
 
-                match processor.process(input_file, mask.as_deref()) {
+                match processor.process(input_file, mask.as_deref(), depth_map.as_deref()) {
                     Ok(result) => {
                         let mut stats_guard = stats_mutex.lock().unwrap();
                         stats_guard.add_result(result.clone());
@@ -3,12 +3,12 @@ use std::fs::File;
 use std::path::{Path, PathBuf};
 use anyhow::anyhow;
 use geo::{ChaikinSmoothing, Polygon, Simplify};
-use image::{DynamicImage, ExtendedColorType, GenericImageView, ImageBuffer, ImageEncoder, ImageResult, Luma};
+use image::{DynamicImage, ExtendedColorType, GenericImageView, GrayImage, ImageBuffer, ImageEncoder, ImageResult, Luma};
 use image::codecs::png::{CompressionType, FilterType, PngEncoder};
-use mimesis::{BinaryImage};
+use mimesis::{border_color, export_svg, fit_bezier, simplify_polygons, smooth_polygon, trace_iso_polygons, AdaptiveMethod, BinaryImage, ColorSpace, Connectivity, MaskChannel, SimplifyTarget, SmoothCurve, SmoothOptions, StructuringElement, ThresholdMethod, TraceOptions};
 use mimesis::draw::DrawMesh;
-use mimesis::mesh::PolygonMesh;
-use crate::config::{Config, MaskMethod};
+use mimesis::mesh::{ExtrusionProfile, PlyFormat, PolygonMesh, Refinement, StlFormat, Triangulator};
+use crate::config::{Config, EdgeProfile, InflateProfile, KeySpace, MaskElement, MaskMethod, OutputFormat, SimplifyMode, SmoothMethod, ThresholdMode, TraceConnectivity, Tracer, Triangulation};
 use crate::stats::{Benchmark, MeshStats, ProcessingResult};
 
 #[cfg(feature = "background-remover")]
@@ -37,7 +37,7 @@ impl Processor {
         false
     }
 
-    pub(crate) fn process(&self, input: &PathBuf, mask: Option<&Path>) -> anyhow::Result<ProcessingResult> {
+    pub(crate) fn process(&self, input: &PathBuf, mask: Option<&Path>, depth_map: Option<&Path>) -> anyhow::Result<ProcessingResult> {
         let mut benchmarks = Benchmark::now();
 
         // Step 1: Load texture image
@@ -51,6 +51,8 @@ impl Processor {
             .to_string_lossy();
 
         // Step 2: Create or load binary mask
+        let mut threshold = None;
+        let mut key_color = None;
         let binary = if let Some(mask_path) = mask {
             let mask_image = image::open(mask_path)
                 .map_err(|e| anyhow!(format!("Failed to open mask image: {}", e)))?;
@@ -67,10 +69,20 @@ impl Processor {
             #[cfg(not(feature = "background-remover"))]
             unreachable!()
         } else {
-            Self::generate_binary_mask(&texture_image, &self.config.processing.mask_method, self.config.processing.threshold)
+            let (binary, chosen_threshold, chosen_key_color) = self.generate_binary_mask(&texture_image)?;
+            threshold = chosen_threshold;
+            key_color = chosen_key_color;
+            binary
         };
         benchmarks.step( "Generate/load mask");
 
+        // Step 2b: Clean up the mask, removing specks, filling pinholes and holes, and dropping extra components
+        let binary = self.clean_mask(binary);
+        if self.config.processing.mask_open_radius > 0 || self.config.processing.mask_close_radius > 0 ||
+            self.config.processing.fill_holes || self.config.processing.max_components > 0 {
+            benchmarks.step( "Clean up mask");
+        }
+
         // Step 3: Setup output directories and save textures
         let file_output_dir = self.config.output.output_folder.to_path_buf();
         let textures_output_dir = file_output_dir.join("textures");
@@ -86,7 +98,7 @@ impl Processor {
 
         let side_texture_filename = if let Some(side_texture_path) = &self.config.output.side_texture {
             let filename = "side.png".to_string();
-            fs::copy(&side_texture_path, textures_output_dir.join(&filename))
+            fs::copy(side_texture_path, textures_output_dir.join(&filename))
                 .map_err(|e| anyhow!(format!("Failed to copy side texture: {}", e)))?;
             filename
         } else {
@@ -95,7 +107,7 @@ impl Processor {
 
         let back_texture_filename = if let Some(back_texture_path) = &self.config.output.back_texture {
             let filename = "back.png".to_string();
-            fs::copy(&back_texture_path, textures_output_dir.join(&filename))
+            fs::copy(back_texture_path, textures_output_dir.join(&filename))
                 .map_err(|e| anyhow!(format!("Failed to copy back texture: {}", e)))?;
             filename
         } else {
@@ -120,8 +132,40 @@ impl Processor {
             benchmarks.step( "Save mask visualization");
         }
 
+        // Step 4b: Save signed distance field
+        if self.config.output.sdf_spread > 0.0 {
+            let sdf = binary.signed_distance_field(self.config.output.sdf_spread);
+            let sdf_image = if self.config.output.sdf_16bit {
+                DynamicImage::ImageLuma16(sdf.to_luma16())
+            } else {
+                DynamicImage::ImageLuma8(sdf.to_luma8())
+            };
+
+            let sdf_path = file_output_dir.join(format!("{}_sdf.png", asset_name));
+            Self::save_uncompressed_png(&sdf_path, &sdf_image)
+                .map_err(|e| anyhow!(format!("Failed to save signed distance field: {}", e)))?;
+            benchmarks.step( "Save signed distance field");
+        }
+
         // Step 5: Convert binary mask to polygons
-        let polygons: Vec<Polygon> = binary.trace_polygons(self.config.processing.min_polygon_dimension);
+        let trace_options = TraceOptions {
+            connectivity: self.connectivity(),
+            min_dimension: self.config.processing.min_polygon_dimension,
+            min_area: self.config.processing.min_component_area,
+            min_hole_dimension: self.config.processing.min_hole_dimension
+                .unwrap_or(self.config.processing.min_polygon_dimension),
+            min_hole_area: self.config.processing.min_hole_area,
+        };
+        let polygons: Vec<Polygon> = match self.config.processing.tracer {
+            #[cfg(feature = "parallel")]
+            Tracer::Corners => binary.par_trace_polygons_with(&trace_options),
+            #[cfg(not(feature = "parallel"))]
+            Tracer::Corners => binary.trace_polygons_with(&trace_options),
+            Tracer::MarchingSquares => {
+                let (field, level) = self.iso_field(&texture_image, &binary, threshold);
+                trace_iso_polygons(&field, level, &trace_options)
+            }
+        };
         benchmarks.step( "Trace polygons");
 
         // Step 6: Process polygon visualization
@@ -136,40 +180,112 @@ impl Processor {
         }
 
         // Step 7: Simplify polygons
-        let simplified_polygons: Vec<Polygon> = if self.config.processing.simplify_tolerance <= 0f64 {
-            polygons
-        }
-        else {
-            let mut simplified_polygons: Vec<Polygon> = Vec::new();
-            for polygon in polygons.iter() {
-                let simplified_polygon = polygon.simplify(&self.config.processing.simplify_tolerance);
-                simplified_polygons.push(simplified_polygon);
+        let mut dropped_polygons = 0;
+        let mut dropped_holes = 0;
+        let mut simplify_tolerance: Option<(f64, f64)> = None;
+        let simplified_polygons: Vec<Polygon> = match self.simplify_target() {
+            SimplifyTarget::Tolerance(tolerance) if tolerance <= 0f64 => polygons,
+            SimplifyTarget::Tolerance(tolerance) if !self.config.processing.preserve_topology => {
+                simplify_tolerance = Some((tolerance, tolerance));
+                let mut simplified_polygons: Vec<Polygon> = Vec::new();
+                for polygon in polygons.iter() {
+                    let simplified_polygon = polygon.simplify(&tolerance);
+                    simplified_polygons.push(simplified_polygon);
+                }
+                simplified_polygons
+            }
+            target => {
+                let mut simplified_polygons: Vec<Polygon> = Vec::new();
+                for simplified in simplify_polygons(&polygons, target) {
+                    dropped_holes += simplified.dropped_holes.len();
+                    for &tolerance in &simplified.tolerances {
+                        let (min, max) = simplify_tolerance.get_or_insert((tolerance, tolerance));
+                        *min = min.min(tolerance);
+                        *max = max.max(tolerance);
+                    }
+                    match simplified.polygon {
+                        Some(simplified_polygon) => simplified_polygons.push(simplified_polygon),
+                        None => dropped_polygons += 1,
+                    }
+                }
+                simplified_polygons
             }
-            simplified_polygons
         };
         benchmarks.step( "Simplify polygons");
 
         // Step 8: Smooth polygons
-        let smooth_polygons: Vec<Polygon> = if self.config.processing.smooth_iterations <= 0 {
-            simplified_polygons
-        }
-        else {
-            let mut smooth_polygons: Vec<Polygon> = Vec::new();
-            for polygon in simplified_polygons.iter() {
-                let smooth_polygon = polygon.chaikin_smoothing(self.config.processing.smooth_iterations);
-                smooth_polygons.push(smooth_polygon);
+        let smooth_polygons: Vec<Polygon> = match self.smooth_curve() {
+            None if self.config.processing.smooth_iterations == 0 => simplified_polygons,
+            None => {
+                let mut smooth_polygons: Vec<Polygon> = Vec::new();
+                for polygon in simplified_polygons.iter() {
+                    let smooth_polygon = polygon.chaikin_smoothing(self.config.processing.smooth_iterations);
+                    smooth_polygons.push(smooth_polygon);
+                }
+                smooth_polygons
+            }
+            Some(curve) => {
+                let smooth_options = SmoothOptions {
+                    curve,
+                    corner_angle: self.config.processing.corner_angle,
+                    segment_length: self.config.processing.smooth_segment_length,
+                };
+                simplified_polygons.iter()
+                    .map(|polygon| smooth_polygon(polygon, &smooth_options))
+                    .collect()
             }
-            smooth_polygons
         };
         benchmarks.step( "Smooth polygons");
 
+        // Step 8b: Save the polygons as vector art
+        if self.config.output.svg {
+            let svg_path = file_output_dir.join(format!("{}.svg", asset_name));
+            let fit_error = self.config.output.svg_fit_error;
+            if fit_error > 0.0 {
+                let curves: Vec<_> = smooth_polygons.iter()
+                    .map(|polygon| fit_bezier(polygon, fit_error, self.config.processing.corner_angle))
+                    .collect();
+                export_svg(&svg_path, width, height, &curves)
+            } else {
+                export_svg(&svg_path, width, height, &smooth_polygons)
+            }.map_err(|e| anyhow!(format!("Failed to save SVG: {}", e)))?;
+            benchmarks.step( "Save SVG");
+        }
+
         // Step 9: Create meshes
         let mut mesh_stats = Vec::new();
+        let mut flat_polygons = 0;
+        let vertex_colors = match self.config.output.format {
+            OutputFormat::Ply | OutputFormat::PlyAscii if self.config.output.ply_vertex_colors => Some(texture_image.to_rgba8()),
+            _ => None,
+        };
+        let inflate = self.config.processing.inflate_height > 0.0;
+        let distances = inflate.then(|| binary.distance_transform());
+        let relief = self.config.processing.relief_scale > 0.0;
+        if inflate && relief {
+            return Err(anyhow!("Inflation and relief cannot be combined"));
+        }
+        let bevel = !matches!(self.config.processing.edge_profile, EdgeProfile::Flat) && self.config.processing.bevel_width > 0.0;
+        if bevel && (inflate || relief) {
+            return Err(anyhow!("Beveled edges cannot be combined with inflation or relief"));
+        }
+        let depth_map = match depth_map {
+            Some(depth_map_path) if relief => Some(image::open(depth_map_path)
+                .map_err(|e| anyhow!(format!("Failed to open depth map: {}", e)))?
+                .to_luma8()),
+            _ if relief => Some(texture_image.to_luma8()),
+            _ => None,
+        };
 
         for (i, polygon) in smooth_polygons.iter().enumerate() {
-            // Create 2D mesh
-            let mesh2d = polygon.mesh2d()
-                .map_err(|e| anyhow!(format!("Failed to create 2D mesh for polygon {}: {}", i, e)))?;
+            // Create 2D mesh, inflated and relief meshes need interior vertices to be displaced
+            let mesh2d = match self.triangulator() {
+                Triangulator::Earcut if inflate || relief =>
+                    polygon.mesh2d_with_interior(self.config.processing.interior_spacing, None),
+                Triangulator::Delaunay(refinement) if inflate || relief =>
+                    polygon.mesh2d_with_interior(self.config.processing.interior_spacing, Some(&refinement)),
+                triangulator => polygon.mesh2d_with(&triangulator),
+            }.map_err(|e| anyhow!(format!("Failed to create 2D mesh for polygon {}: {}", i, e)))?;
 
             let vertex_count_2d = mesh2d.get_vertices().len();
             let triangle_count_2d = mesh2d.get_indices().len() / 3;
@@ -181,20 +297,100 @@ impl Processor {
             }
 
             // Create 3D mesh
-            let mesh3d = mesh2d.extrude(self.config.processing.extrude_height, width as f64, height as f64);
+            let profile = match self.config.processing.edge_profile {
+                EdgeProfile::Flat => ExtrusionProfile::Flat,
+                EdgeProfile::Chamfer => ExtrusionProfile::Chamfer { width: self.config.processing.bevel_width },
+                EdgeProfile::Round => ExtrusionProfile::Round {
+                    width: self.config.processing.bevel_width,
+                    segments: self.config.processing.bevel_segments,
+                },
+            };
+            let mut mesh3d = if let Some(depth_map) = &depth_map {
+                mesh2d.relief(self.config.processing.extrude_height, width as f64, height as f64, depth_map,
+                              self.config.processing.relief_scale)
+            } else if let Some(distances) = &distances {
+                let inflate_profile = match self.config.processing.inflate_profile {
+                    InflateProfile::Circular => mimesis::mesh::InflateProfile::Circular,
+                    InflateProfile::Parabolic => mimesis::mesh::InflateProfile::Parabolic,
+                    InflateProfile::Linear => mimesis::mesh::InflateProfile::Linear,
+                };
+                mesh2d.inflate(self.config.processing.extrude_height, width as f64, height as f64, distances,
+                               self.config.processing.inflate_height, inflate_profile)
+            } else {
+                // Polygons that cannot be beveled keep flat edges rather than failing the image
+                mesh2d.extrude_profile(self.config.processing.extrude_height, width as f64, height as f64, profile, &self.triangulator())
+                    .map_err(|e| anyhow!(format!("Failed to extrude polygon {}: {}", i, e)))?
+                    .unwrap_or_else(|| {
+                        flat_polygons += 1;
+                        mesh2d.extrude(self.config.processing.extrude_height, width as f64, height as f64)
+                    })
+            };
+            mesh3d.compute_normals(self.config.processing.crease_angle.to_radians());
             let vertex_count_3d = mesh3d.get_vertices().len();
             let triangle_count_3d = mesh3d.get_faces().iter().map(|group| group.indices.len()).sum();
 
-            let mesh_path = file_output_dir.join(format!("{}_{}.obj", asset_name, i));
-            let material_path = file_output_dir.join(format!("{}_{}.mtl", asset_name, i));
-
-            mesh3d.export_obj(
-                mesh_path.as_path(),
-                material_path.as_path(),
-                &front_texture_filename,
-                &back_texture_filename,
-                &side_texture_filename
-            ).map_err(|e| anyhow!(format!("Failed to export 3D mesh: {}", e)))?;
+            match self.config.output.format {
+                OutputFormat::Obj => {
+                    let mesh_path = file_output_dir.join(format!("{}_{}.obj", asset_name, i));
+                    let material_path = file_output_dir.join(format!("{}_{}.mtl", asset_name, i));
+
+                    mesh3d.export_obj(
+                        mesh_path.as_path(),
+                        material_path.as_path(),
+                        &front_texture_filename,
+                        &back_texture_filename,
+                        &side_texture_filename
+                    ).map_err(|e| anyhow!(format!("Failed to export 3D mesh: {}", e)))?;
+                }
+                OutputFormat::Gltf => {
+                    let mesh_path = file_output_dir.join(format!("{}_{}.gltf", asset_name, i));
+
+                    mesh3d.export_gltf(
+                        mesh_path.as_path(),
+                        &front_texture_filename,
+                        &back_texture_filename,
+                        &side_texture_filename
+                    ).map_err(|e| anyhow!(format!("Failed to export 3D mesh: {}", e)))?;
+                }
+                OutputFormat::Glb => {
+                    let mesh_path = file_output_dir.join(format!("{}_{}.glb", asset_name, i));
+
+                    mesh3d.export_glb(
+                        mesh_path.as_path(),
+                        &textures_output_dir.join(&front_texture_filename),
+                        &textures_output_dir.join(&back_texture_filename),
+                        &textures_output_dir.join(&side_texture_filename)
+                    ).map_err(|e| anyhow!(format!("Failed to export 3D mesh: {}", e)))?;
+                }
+                OutputFormat::Stl | OutputFormat::StlAscii => {
+                    let mesh_path = file_output_dir.join(format!("{}_{}.stl", asset_name, i));
+                    let stl_format = if let OutputFormat::StlAscii = self.config.output.format {
+                        StlFormat::Ascii
+                    } else {
+                        StlFormat::Binary
+                    };
+
+                    mesh3d.export_stl(
+                        mesh_path.as_path(),
+                        stl_format,
+                        self.config.processing.mm_per_pixel
+                    ).map_err(|e| anyhow!(format!("Failed to export 3D mesh: {}", e)))?;
+                }
+                OutputFormat::Ply | OutputFormat::PlyAscii => {
+                    let mesh_path = file_output_dir.join(format!("{}_{}.ply", asset_name, i));
+                    let ply_format = if let OutputFormat::PlyAscii = self.config.output.format {
+                        PlyFormat::Ascii
+                    } else {
+                        PlyFormat::BinaryLittleEndian
+                    };
+
+                    mesh3d.export_ply(
+                        mesh_path.as_path(),
+                        ply_format,
+                        vertex_colors.as_ref()
+                    ).map_err(|e| anyhow!(format!("Failed to export 3D mesh: {}", e)))?;
+                }
+            }
 
             mesh_stats.push(MeshStats {
                 vertex_count_2d,
@@ -211,52 +407,179 @@ impl Processor {
             input: input.to_path_buf(), 
             width, height,
             polygon_count: smooth_polygons.len(),
+            threshold,
+            key_color,
+            simplify_tolerance,
+            dropped_polygons,
+            dropped_holes,
+            flat_polygons,
             mesh_stats,
             benchmarks,
             total_duration,
         })
     }
 
-    fn generate_binary_mask(image: &DynamicImage, method: &MaskMethod, threshold: u8) -> BinaryImage {
-        match method {
-            MaskMethod::Luminance => {
-                let gray = image.to_luma8();
-                let binary_data: Vec<u8> = gray.pixels()
-                    .map(|pixel| if pixel.0[0] > threshold { 255 } else { 0 })
-                    .collect();
-                BinaryImage::from_raw(gray.width(), gray.height(), &binary_data)
-            },
-            MaskMethod::Alpha => {
-                let rgba = image.to_rgba8();
-                let binary_data: Vec<u8> = rgba.pixels()
-                    .map(|pixel| if pixel.0[3] > threshold { 255 } else { 0 })
-                    .collect();
-                BinaryImage::from_raw(rgba.width(), rgba.height(), &binary_data)
-            },
-            MaskMethod::Red => {
-                let rgb = image.to_rgb8();
-                let binary_data: Vec<u8> = rgb.pixels()
-                    .map(|pixel| if pixel.0[0] > threshold { 255 } else { 0 })
-                    .collect();
-                BinaryImage::from_raw(rgb.width(), rgb.height(), &binary_data)
-            },
-            MaskMethod::Green => {
-                let rgb = image.to_rgb8();
-                let binary_data: Vec<u8> = rgb.pixels()
-                    .map(|pixel| if pixel.0[1] > threshold { 255 } else { 0 })
-                    .collect();
-                BinaryImage::from_raw(rgb.width(), rgb.height(), &binary_data)
-            },
-            MaskMethod::Blue => {
-                let rgb = image.to_rgb8();
-                let binary_data: Vec<u8> = rgb.pixels()
-                    .map(|pixel| if pixel.0[2] > threshold { 255 } else { 0 })
-                    .collect();
-                BinaryImage::from_raw(rgb.width(), rgb.height(), &binary_data)
-            },
+    fn clean_mask(&self, binary: BinaryImage) -> BinaryImage {
+        let element = |radius| match self.config.processing.mask_element {
+            MaskElement::Square => StructuringElement::Square(radius),
+            MaskElement::Cross => StructuringElement::Cross(radius),
+            MaskElement::Disk => StructuringElement::Disk(radius),
+        };
+
+        let mut binary = binary;
+        if self.config.processing.mask_open_radius > 0 {
+            binary = binary.open(element(self.config.processing.mask_open_radius));
+        }
+        if self.config.processing.mask_close_radius > 0 {
+            binary = binary.close(element(self.config.processing.mask_close_radius));
+        }
+        if self.config.processing.fill_holes {
+            let max_area = self.config.processing.fill_holes_max_area;
+            binary = binary.fill_holes((max_area > 0).then_some(max_area), self.connectivity());
+        }
+        if self.config.processing.max_components > 0 {
+            binary = binary.keep_largest_components(self.config.processing.max_components, self.connectivity());
+        }
+        binary
+    }
+
+    /// Generate the mask from the texture, along with the threshold or the key color used.
+    fn generate_binary_mask(&self, image: &DynamicImage) -> anyhow::Result<(BinaryImage, Option<u8>, Option<[u8; 3]>)> {
+        if let Some(channel) = self.mask_channel() {
+            let method = match self.config.processing.threshold_method {
+                ThresholdMode::Fixed => ThresholdMethod::Fixed(self.config.processing.threshold),
+                ThresholdMode::Otsu => ThresholdMethod::Otsu,
+                ThresholdMode::Triangle => ThresholdMethod::Triangle,
+                ThresholdMode::Percentile => ThresholdMethod::Percentile(self.config.processing.threshold_percentile),
+            };
+            let (binary, threshold) = BinaryImage::from_channel(image, channel, method);
+            return Ok((binary, Some(threshold), None));
+        }
+
+        match self.config.processing.mask_method {
+            MaskMethod::ChromaKey => {
+                let key_color = Self::parse_hex_color(&self.config.processing.key_color)?;
+                Ok((self.generate_color_key_mask(image, key_color), None, Some(key_color)))
+            }
+            MaskMethod::BorderColor => {
+                let key_color = border_color(image);
+                Ok((self.generate_color_key_mask(image, key_color), None, Some(key_color)))
+            }
+            MaskMethod::AdaptiveMean => Ok((self.generate_adaptive_mask(image, AdaptiveMethod::Mean), None, None)),
+            MaskMethod::AdaptiveGaussian => Ok((self.generate_adaptive_mask(image, AdaptiveMethod::Gaussian), None, None)),
+            MaskMethod::Luminance | MaskMethod::Alpha | MaskMethod::Red | MaskMethod::Green | MaskMethod::Blue => {
+                unreachable!("Channel mask methods are handled above")
+            }
+        }
+    }
+
+    fn simplify_target(&self) -> SimplifyTarget {
+        let processing = &self.config.processing;
+        match processing.simplify_mode {
+            SimplifyMode::Absolute => SimplifyTarget::Tolerance(processing.simplify_tolerance),
+            SimplifyMode::Relative => SimplifyTarget::RelativeTolerance(processing.relative_tolerance),
+            SimplifyMode::PolygonBudget => SimplifyTarget::PolygonBudget(processing.vertex_budget),
+            SimplifyMode::ImageBudget => SimplifyTarget::TotalBudget(processing.vertex_budget),
+        }
+    }
+
+    fn triangulator(&self) -> Triangulator {
+        let processing = &self.config.processing;
+        match processing.triangulation {
+            Triangulation::Earcut => Triangulator::Earcut,
+            Triangulation::Delaunay => Triangulator::Delaunay(Refinement {
+                min_angle: processing.min_angle,
+                max_area: (processing.max_triangle_area > 0.0).then_some(processing.max_triangle_area),
+                max_steiner_points: processing.max_steiner_points,
+            }),
         }
     }
 
+    /// Spline fitted by the smooth method, `None` for Chaikin smoothing.
+    fn smooth_curve(&self) -> Option<SmoothCurve> {
+        match self.config.processing.smooth_method {
+            SmoothMethod::Chaikin => None,
+            SmoothMethod::CatmullRom => Some(SmoothCurve::CatmullRom),
+            SmoothMethod::BSpline => Some(SmoothCurve::BSpline),
+        }
+    }
+
+    fn connectivity(&self) -> Connectivity {
+        match self.config.processing.connectivity {
+            TraceConnectivity::Four => Connectivity::Four,
+            TraceConnectivity::Eight => Connectivity::Eight,
+        }
+    }
+
+    /// Channel thresholded by the mask method, if it thresholds one.
+    fn mask_channel(&self) -> Option<MaskChannel> {
+        match self.config.processing.mask_method {
+            MaskMethod::Luminance => Some(MaskChannel::Luminance),
+            MaskMethod::Alpha => Some(MaskChannel::Alpha),
+            MaskMethod::Red => Some(MaskChannel::Red),
+            MaskMethod::Green => Some(MaskChannel::Green),
+            MaskMethod::Blue => Some(MaskChannel::Blue),
+            MaskMethod::ChromaKey | MaskMethod::BorderColor |
+            MaskMethod::AdaptiveMean | MaskMethod::AdaptiveGaussian => None,
+        }
+    }
+
+    /// Grayscale field traced by marching squares along with its iso-level, agreeing with
+    /// the mask.
+    ///
+    /// The field is the thresholded channel when the mask was generated from one, so that
+    /// the contours follow its anti-aliased edges, and the mask itself otherwise. Pixels
+    /// changed by the mask cleanup take the value of the mask.
+    fn iso_field(&self, texture_image: &DynamicImage, binary: &BinaryImage, threshold: Option<u8>) -> (GrayImage, f64) {
+        let (field, level) = match (threshold, self.mask_channel()) {
+            (Some(threshold), Some(channel)) => {
+                (channel.extract(texture_image), threshold as f64 + 0.5)
+            }
+            _ => (GrayImage::new(binary.width(), binary.height()), 127.5),
+        };
+
+        let field = GrayImage::from_fn(binary.width(), binary.height(), |x, y| {
+            let value = field.get_pixel(x, y).0[0];
+            let foreground = *binary.get_pixel(x, y);
+            if (value as f64 > level) == foreground {
+                Luma([value])
+            } else if foreground {
+                Luma([u8::MAX])
+            } else {
+                Luma([0])
+            }
+        });
+        (field, level)
+    }
+
+    fn generate_color_key_mask(&self, image: &DynamicImage, key_color: [u8; 3]) -> BinaryImage {
+        let space = match self.config.processing.key_space {
+            KeySpace::Lab => ColorSpace::Lab,
+            KeySpace::Hsv => ColorSpace::Hsv,
+        };
+        BinaryImage::from_color_key(image, key_color, self.config.processing.key_tolerance, space)
+    }
+
+    fn generate_adaptive_mask(&self, image: &DynamicImage, method: AdaptiveMethod) -> BinaryImage {
+        BinaryImage::from_channel_adaptive(image, MaskChannel::Luminance, method,
+            self.config.processing.adaptive_window, self.config.processing.adaptive_offset)
+    }
+
+    /// Parse a color given as `#rrggbb`, the `#` being optional.
+    fn parse_hex_color(color: &str) -> anyhow::Result<[u8; 3]> {
+        let hex = color.trim().trim_start_matches('#');
+        if hex.len() != 6 || !hex.is_ascii() {
+            return Err(anyhow!(format!("Invalid key color '{}', expected #rrggbb", color)));
+        }
+
+        let mut rgb = [0u8; 3];
+        for (i, value) in rgb.iter_mut().enumerate() {
+            *value = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16)
+                .map_err(|_| anyhow!(format!("Invalid key color '{}', expected #rrggbb", color)))?;
+        }
+        Ok(rgb)
+    }
+
     fn get_extended_color_type(image: &DynamicImage) -> ExtendedColorType {
         match image {
             DynamicImage::ImageLuma8(_) => ExtendedColorType::L8,
@@ -288,4 +611,21 @@ impl Processor {
             Self::get_extended_color_type(image),
         )
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Processor;
+
+    #[test]
+    fn parse_hex_color_accepts_six_digits() {
+        assert_eq!(Processor::parse_hex_color("#00b140").unwrap(), [0x00, 0xb1, 0x40]);
+        assert_eq!(Processor::parse_hex_color("FFfe01").unwrap(), [0xff, 0xfe, 0x01]);
+        assert_eq!(Processor::parse_hex_color(" #102030 ").unwrap(), [0x10, 0x20, 0x30]);
+
+        for invalid in ["#00b14", "00b1400", "#00g140", "", "#", "ééé", "#0€b1"] {
+            assert!(Processor::parse_hex_color(invalid).is_err(), "{:?} was accepted", invalid);
+        }
+    }
+}
+
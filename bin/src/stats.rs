@@ -57,6 +57,18 @@ pub(crate) struct ProcessingResult {
     pub(crate) width: u32,
     pub(crate) height: u32,
     pub(crate) polygon_count: usize,
+    /// Threshold used to generate the mask, if it was not loaded
+    pub(crate) threshold: Option<u8>,
+    /// Color keyed out of the mask, with the color key mask methods
+    pub(crate) key_color: Option<[u8; 3]>,
+    /// Smallest and largest tolerance the rings were simplified with, if they were
+    pub(crate) simplify_tolerance: Option<(f64, f64)>,
+    /// Polygons whose exterior degenerated when simplified
+    pub(crate) dropped_polygons: usize,
+    /// Holes that degenerated when simplified, outside of the dropped polygons
+    pub(crate) dropped_holes: usize,
+    /// Polygons extruded with flat edges because no valid bevel was found for them
+    pub(crate) flat_polygons: usize,
     pub(crate) mesh_stats: Vec<MeshStats>,
     pub(crate) benchmarks: Benchmark,
     pub(crate) total_duration: Duration,
@@ -89,7 +101,24 @@ impl ProcessingResult {
         println!("✓ PROCESSING COMPLETE");
         println!("  File: {}", self.input.display());
         println!("  Image: {}×{} pixels", self.width, self.height);
+        if let Some(threshold) = self.threshold {
+            println!("  Mask Threshold: {}", threshold);
+        }
+        if let Some([r, g, b]) = self.key_color {
+            println!("  Key Color: #{:02x}{:02x}{:02x}", r, g, b);
+        }
         println!("  Polygons: {}", self.polygon_count);
+        match self.simplify_tolerance {
+            Some((min, max)) if min == max => println!("  Simplify Tolerance: {:.2}px", min),
+            Some((min, max)) => println!("  Simplify Tolerance: {:.2}px to {:.2}px", min, max),
+            None => (),
+        }
+        if self.dropped_polygons > 0 || self.dropped_holes > 0 {
+            println!("  Degenerate Rings Dropped: {} polygons, {} holes", self.dropped_polygons, self.dropped_holes);
+        }
+        if self.flat_polygons > 0 {
+            println!("  Bevel Too Wide: {} polygons extruded with flat edges", self.flat_polygons);
+        }
         println!("  Total Time: {:.3}s", self.total_duration.as_secs_f64());
 
         if show_mesh_details && !self.mesh_stats.is_empty() {
@@ -321,7 +350,7 @@ impl ProcessingStats {
 
             // Sort by total time descending
             let mut sorted_benchmarks = self.benchmarks_summary.clone();
-            sorted_benchmarks.sort_by(|a, b| b.1.cmp(&a.1));
+            sorted_benchmarks.sort_by_key(|b| std::cmp::Reverse(b.1));
 
             let total_time = self.total_processing_time.as_secs_f64();
 
@@ -0,0 +1,239 @@
+use geo::{Coord, LineString, Polygon};
+use crate::smooth::turn;
+
+/// Cubic Bézier curve from `start` to `end`, pulled towards its two control points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CubicBezier {
+    pub start: Coord,
+    pub control1: Coord,
+    pub control2: Coord,
+    pub end: Coord,
+}
+
+impl CubicBezier {
+    /// Straight curve, with its control points a third of the way along the tangents.
+    fn straight(start: Coord, end: Coord, start_tangent: Coord, end_tangent: Coord) -> Self {
+        let third = distance(start, end) / 3.0;
+        CubicBezier {
+            start,
+            control1: start + start_tangent * third,
+            control2: end + end_tangent * third,
+            end,
+        }
+    }
+
+    /// Point of the curve at `t`, from 0 at the start to 1 at the end.
+    pub fn point(&self, t: f64) -> Coord {
+        let s = 1.0 - t;
+        self.start * (s * s * s) + self.control1 * (3.0 * s * s * t) + self.control2 * (3.0 * s * t * t)
+            + self.end * (t * t * t)
+    }
+
+    fn derivative(&self, t: f64) -> Coord {
+        let s = 1.0 - t;
+        (self.control1 - self.start) * (3.0 * s * s) + (self.control2 - self.control1) * (6.0 * s * t)
+            + (self.end - self.control2) * (3.0 * t * t)
+    }
+
+    fn second_derivative(&self, t: f64) -> Coord {
+        (self.control2 - self.control1 * 2.0 + self.start) * (6.0 * (1.0 - t))
+            + (self.end - self.control2 * 2.0 + self.control1) * (6.0 * t)
+    }
+}
+
+/// Rings of a polygon fitted with cubic Bézier curves, each curve of a ring starting where the
+/// previous one ends and the last one ending where the first one starts
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BezierPolygon {
+    pub exterior: Vec<CubicBezier>,
+    pub interiors: Vec<Vec<CubicBezier>>,
+}
+
+/// Fit the rings of a polygon with cubic Bézier curves, following Schneider's algorithm.
+///
+/// The vertices where a ring turns by more than `corner_angle` degrees are corners, joining
+/// curves with different tangents. Each run of vertices between two corners is fitted with
+/// as few curves as it takes to pass within `max_error` pixels of every vertex, joined
+/// smoothly. Since pixel traced contours turn by 90 degrees at every vertex, they are best
+/// simplified or smoothed first.
+pub fn fit_bezier(polygon: &Polygon, max_error: f64, corner_angle: f64) -> BezierPolygon {
+    BezierPolygon {
+        exterior: fit_ring(polygon.exterior(), max_error, corner_angle),
+        interiors: polygon.interiors().iter()
+            .map(|ring| fit_ring(ring, max_error, corner_angle))
+            .collect(),
+    }
+}
+
+fn fit_ring(ring: &LineString, max_error: f64, corner_angle: f64) -> Vec<CubicBezier> {
+    let mut points = ring.0.clone();
+    points.dedup();
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    let n = points.len();
+    let mut curves = Vec::new();
+    if n < 3 {
+        for i in 0..n {
+            let (start, end) = (points[i], points[(i + 1) % n]);
+            let tangent = unit(end - start);
+            curves.push(CubicBezier::straight(start, end, tangent, -tangent));
+        }
+        return curves;
+    }
+
+    let corners: Vec<usize> = (0..n)
+        .filter(|&i| turn(points[(i + n - 1) % n], points[i], points[(i + 1) % n]) > corner_angle)
+        .collect();
+
+    if corners.is_empty() {
+        // A single run around the ring, leaving its first vertex along the same tangent as
+        // it comes back
+        let tangent = unit(points[1] - points[n - 1]);
+        let run: Vec<Coord> = points.iter().copied().chain([points[0]]).collect();
+        fit_cubic(&run, tangent, -tangent, max_error * max_error, &mut curves);
+    } else {
+        for (k, &corner) in corners.iter().enumerate() {
+            let next = corners[(k + 1) % corners.len()];
+            let length = (next + n - corner - 1) % n + 1;
+            let run: Vec<Coord> = (0..=length).map(|i| points[(corner + i) % n]).collect();
+            let (start_tangent, end_tangent) = (unit(run[1] - run[0]), unit(run[length - 1] - run[length]));
+            fit_cubic(&run, start_tangent, end_tangent, max_error * max_error, &mut curves);
+        }
+    }
+    curves
+}
+
+/// Fit the points with a curve leaving the first one along `start_tangent` and reaching the
+/// last one against `end_tangent`, splitting them at the worst fitted point until the curves
+/// are within the error.
+fn fit_cubic(points: &[Coord], start_tangent: Coord, end_tangent: Coord, max_squared_error: f64, curves: &mut Vec<CubicBezier>) {
+    let last = points.len() - 1;
+    if last == 1 {
+        curves.push(CubicBezier::straight(points[0], points[1], start_tangent, end_tangent));
+        return;
+    }
+
+    let mut parameters = chord_length_parameters(points);
+    let mut curve = least_squares_curve(points, &parameters, start_tangent, end_tangent);
+    let (mut error, mut split) = worst_point(points, &parameters, &curve);
+
+    // Close fits are worth improving by moving the points along the curve before splitting
+    if error <= 4.0 * max_squared_error {
+        for _ in 0..4 {
+            if error <= max_squared_error {
+                break;
+            }
+            reparameterize(points, &mut parameters, &curve);
+            curve = least_squares_curve(points, &parameters, start_tangent, end_tangent);
+            (error, split) = worst_point(points, &parameters, &curve);
+        }
+    }
+    if error <= max_squared_error {
+        curves.push(curve);
+        return;
+    }
+
+    let mut center_tangent = unit(points[split - 1] - points[split + 1]);
+    if center_tangent == Coord::zero() {
+        center_tangent = unit(points[split - 1] - points[split]);
+    }
+    fit_cubic(&points[..=split], start_tangent, center_tangent, max_squared_error, curves);
+    fit_cubic(&points[split..], -center_tangent, end_tangent, max_squared_error, curves);
+}
+
+/// Parameters of the points along the curve, proportional to the length of the polyline.
+fn chord_length_parameters(points: &[Coord]) -> Vec<f64> {
+    let mut parameters = Vec::with_capacity(points.len());
+    let mut length = 0.0;
+    parameters.push(length);
+    for pair in points.windows(2) {
+        length += distance(pair[0], pair[1]);
+        parameters.push(length);
+    }
+    for parameter in &mut parameters {
+        *parameter /= length;
+    }
+    parameters
+}
+
+/// Curve with the given end points and tangents whose distance to the points at their
+/// parameters is the smallest in the least squares sense.
+fn least_squares_curve(points: &[Coord], parameters: &[f64], start_tangent: Coord, end_tangent: Coord) -> CubicBezier {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let mut c = [[0.0; 2]; 2];
+    let mut x = [0.0; 2];
+    for (&point, &t) in points.iter().zip(parameters) {
+        let s = 1.0 - t;
+        let (b0, b1, b2, b3) = (s * s * s, 3.0 * s * s * t, 3.0 * s * t * t, t * t * t);
+        let a = [start_tangent * b1, end_tangent * b2];
+        let offset = point - (first * (b0 + b1) + last * (b2 + b3));
+        c[0][0] += dot(a[0], a[0]);
+        c[0][1] += dot(a[0], a[1]);
+        c[1][1] += dot(a[1], a[1]);
+        x[0] += dot(a[0], offset);
+        x[1] += dot(a[1], offset);
+    }
+    c[1][0] = c[0][1];
+
+    let determinant = c[0][0] * c[1][1] - c[1][0] * c[0][1];
+    let (start_length, end_length) = if determinant != 0.0 {
+        (
+            (x[0] * c[1][1] - x[1] * c[0][1]) / determinant,
+            (c[0][0] * x[1] - c[1][0] * x[0]) / determinant,
+        )
+    } else {
+        (0.0, 0.0)
+    };
+
+    // Tangents too short or backwards give loops, a straight curve is a safer start
+    let epsilon = 1e-6 * distance(first, last);
+    if start_length < epsilon || end_length < epsilon {
+        return CubicBezier::straight(first, last, start_tangent, end_tangent);
+    }
+    CubicBezier {
+        start: first,
+        control1: first + start_tangent * start_length,
+        control2: last + end_tangent * end_length,
+        end: last,
+    }
+}
+
+/// Largest squared distance between an inner point and the curve at its parameter, along
+/// with the index of that point.
+fn worst_point(points: &[Coord], parameters: &[f64], curve: &CubicBezier) -> (f64, usize) {
+    let mut worst = (0.0, points.len() / 2);
+    for i in 1..points.len() - 1 {
+        let offset = curve.point(parameters[i]) - points[i];
+        let error = dot(offset, offset);
+        if error > worst.0 {
+            worst = (error, i);
+        }
+    }
+    worst
+}
+
+/// Move the parameters closer to the nearest point of the curve with a Newton-Raphson step.
+fn reparameterize(points: &[Coord], parameters: &mut [f64], curve: &CubicBezier) {
+    for (&point, t) in points.iter().zip(parameters.iter_mut()) {
+        let offset = curve.point(*t) - point;
+        let (d1, d2) = (curve.derivative(*t), curve.second_derivative(*t));
+        let denominator = dot(d1, d1) + dot(offset, d2);
+        if denominator != 0.0 {
+            *t = (*t - dot(offset, d1) / denominator).clamp(0.0, 1.0);
+        }
+    }
+}
+
+fn dot(a: Coord, b: Coord) -> f64 {
+    a.x * b.x + a.y * b.y
+}
+
+fn distance(a: Coord, b: Coord) -> f64 {
+    (b - a).x.hypot((b - a).y)
+}
+
+fn unit(vector: Coord) -> Coord {
+    let length = vector.x.hypot(vector.y);
+    if length > 0.0 { vector / length } else { Coord::zero() }
+}
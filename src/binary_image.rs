@@ -70,6 +70,20 @@ impl BinaryImage {
         GenericImageView::get_pixel(self, x, y)
     }
 
+    /// Swap the foreground and the background.
+    #[must_use]
+    pub fn invert(&self) -> BinaryImage {
+        let mut buffer = self.buffer.clone();
+        buffer.negate();
+        BinaryImage::from_bitvec(self.width, self.height, buffer)
+    }
+
+    /// Pixels in row major order
+    #[inline]
+    pub(crate) fn bits(&self) -> &BitVec {
+        &self.buffer
+    }
+
     #[inline]
     #[must_use]
     pub fn height(&self) -> u32 {
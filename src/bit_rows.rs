@@ -0,0 +1,161 @@
+use bit_vec::BitVec;
+use crate::binary_image::BinaryImage;
+
+/// Copy of a [`BinaryImage`] where every row starts on a new 64 bit word, so that rows can be
+/// processed a word at a time.
+///
+/// Bit `x` of a row is bit `x % 64` of its word `x / 64`. Bits past the width of the image
+/// are always zero.
+#[derive(Debug, Clone)]
+pub(crate) struct BitRows {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitRows {
+    pub(crate) fn zeros(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(64);
+        Self {
+            width,
+            height,
+            words_per_row,
+            words: vec![0; words_per_row * height],
+        }
+    }
+
+    pub(crate) fn from_image(image: &BinaryImage) -> Self {
+        let mut rows = Self::zeros(image.width() as usize, image.height() as usize);
+        let storage = image.bits().storage();
+        let width = rows.width;
+
+        for y in 0..rows.height {
+            let offset = y * width;
+            for (j, word) in rows.row_mut(y).iter_mut().enumerate() {
+                let start = j * 64;
+                *word = read_bits(storage, offset + start, (width - start).min(64));
+            }
+        }
+        rows
+    }
+
+    pub(crate) fn to_image(&self) -> BinaryImage {
+        let len = self.width * self.height;
+        let mut blocks = vec![0u32; len.div_ceil(32)];
+        for y in 0..self.height {
+            let offset = y * self.width;
+            for (j, &word) in self.row(y).iter().enumerate() {
+                write_bits(&mut blocks, offset + j * 64, word);
+            }
+        }
+
+        let mut buffer = BitVec::from_elem(len, false);
+        // SAFETY: the storage keeps its length, and bits past `len` are left to zero since
+        // they are past the width of the last row
+        unsafe { buffer.storage_mut().copy_from_slice(&blocks) };
+        BinaryImage::from_bitvec(self.width as u32, self.height as u32, buffer)
+    }
+
+    #[inline]
+    pub(crate) fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    pub(crate) fn height(&self) -> usize {
+        self.height
+    }
+
+    #[inline]
+    pub(crate) fn words_per_row(&self) -> usize {
+        self.words_per_row
+    }
+
+    #[inline]
+    pub(crate) fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    #[inline]
+    pub(crate) fn row_mut(&mut self, y: usize) -> &mut [u64] {
+        &mut self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    /// Mask of the bits of the last word of a row that lie inside of the image
+    #[inline]
+    pub(crate) fn last_word_mask(&self) -> u64 {
+        match self.width % 64 {
+            0 => u64::MAX,
+            bits => (1 << bits) - 1,
+        }
+    }
+}
+
+/// Columns of the vertical cracks of a row of [`BitRows`], from left to right.
+///
+/// Crack `x` lies between the pixels `x - 1` and `x`, the crack on the right of the image
+/// being `width`. Words without any change of pixel are skipped at once. Since the row is
+/// surrounded by background, cracks come in pairs bounding the runs of foreground pixels.
+pub(crate) struct RowCracks<'a> {
+    row: &'a [u64],
+    /// Index of the next word to read
+    index: usize,
+    /// Cracks of the last word read, not returned yet
+    cracks: u64,
+    /// Last pixel of the last word read
+    carry: u64,
+}
+
+impl<'a> RowCracks<'a> {
+    pub(crate) fn new(row: &'a [u64]) -> Self {
+        RowCracks { row, index: 0, cracks: 0, carry: 0 }
+    }
+}
+
+impl Iterator for RowCracks<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.cracks == 0 {
+            // The word past the row is background, closing a row ending on a full word
+            if self.index > self.row.len() {
+                return None;
+            }
+            let word = self.row.get(self.index).copied().unwrap_or(0);
+            self.cracks = word ^ ((word << 1) | self.carry);
+            self.carry = word >> 63;
+            self.index += 1;
+        }
+
+        let x = (self.index - 1) * 64 + self.cracks.trailing_zeros() as usize;
+        self.cracks &= self.cracks - 1;
+        Some(x)
+    }
+}
+
+/// Read `len` bits, at most 64, starting at bit `start` of a `BitVec` storage.
+fn read_bits(storage: &[u32], start: usize, len: usize) -> u64 {
+    let block = start / 32;
+    let mut window = 0u128;
+    for k in 0..3 {
+        if let Some(&bits) = storage.get(block + k) {
+            window |= (bits as u128) << (32 * k);
+        }
+    }
+
+    let value = (window >> (start % 32)) as u64;
+    if len >= 64 { value } else { value & ((1 << len) - 1) }
+}
+
+/// Set the bits of `value` starting at bit `start` of a zeroed `BitVec` storage.
+fn write_bits(storage: &mut [u32], start: usize, value: u64) {
+    let block = start / 32;
+    let window = (value as u128) << (start % 32);
+    for k in 0..3 {
+        let bits = (window >> (32 * k)) as u32;
+        if bits != 0 {
+            storage[block + k] |= bits;
+        }
+    }
+}
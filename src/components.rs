@@ -0,0 +1,275 @@
+use bit_vec::BitVec;
+use image::{ImageBuffer, Luma};
+use crate::binary_image::BinaryImage;
+use crate::bit_rows::{BitRows, RowCracks};
+
+/// Pixels considered adjacent to each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Connectivity {
+    /// Pixels sharing an edge
+    #[default]
+    Four,
+    /// Pixels sharing an edge or a corner
+    Eight,
+}
+
+impl Connectivity {
+    /// Connectivity of the background when the foreground uses this one, so that neither
+    /// crosses the other where two pixels touch diagonally
+    #[inline]
+    #[must_use]
+    pub(crate) fn dual(self) -> Connectivity {
+        match self {
+            Connectivity::Four => Connectivity::Eight,
+            Connectivity::Eight => Connectivity::Four,
+        }
+    }
+}
+
+/// Statistics of a connected component of the foreground
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentStats {
+    /// Label of the component in the label map, starting at 1
+    pub label: u32,
+    /// Number of pixels
+    pub area: usize,
+    /// Left column of the bounding box
+    pub x: u32,
+    /// Top row of the bounding box
+    pub y: u32,
+    /// Width of the bounding box
+    pub width: u32,
+    /// Height of the bounding box
+    pub height: u32,
+    /// Mean position of the pixels, pixel centers lie at half coordinates
+    pub centroid: (f64, f64),
+}
+
+/// Connected components of a [`BinaryImage`], see [`BinaryImage::label_components`]
+#[derive(Debug, Clone)]
+pub struct Components {
+    labels: ImageBuffer<Luma<u32>, Vec<u32>>,
+    stats: Vec<ComponentStats>,
+}
+
+impl Components {
+    /// Label of the component of every pixel, 0 for the background
+    #[inline]
+    #[must_use]
+    pub fn labels(&self) -> &ImageBuffer<Luma<u32>, Vec<u32>> {
+        &self.labels
+    }
+
+    /// Statistics of every component, `stats()[label - 1]` describes the component `label`
+    #[inline]
+    #[must_use]
+    pub fn stats(&self) -> &[ComponentStats] {
+        &self.stats
+    }
+
+    /// Number of components
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.stats.len()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.stats.is_empty()
+    }
+
+    /// Mask of the pixels of the components for which `keep` returns true.
+    pub fn select<F>(&self, keep: F) -> BinaryImage
+    where
+        F: Fn(&ComponentStats) -> bool,
+    {
+        let kept: Vec<bool> = std::iter::once(false)
+            .chain(self.stats.iter().map(keep))
+            .collect();
+        let buffer: BitVec = self.labels.iter().map(|&label| kept[label as usize]).collect();
+        BinaryImage::from_bitvec(self.labels.width(), self.labels.height(), buffer)
+    }
+}
+
+impl BinaryImage {
+    /// Label the connected components of the foreground.
+    ///
+    /// Components are numbered from 1 in the order their first pixel is met when scanning
+    /// the image row by row, background pixels are labelled 0.
+    pub fn label_components(&self, connectivity: Connectivity) -> Components {
+        let rows = BitRows::from_image(self);
+        let (width, height) = (rows.width(), rows.height());
+
+        // Runs touching diagonally are adjacent with an 8-connectivity
+        let reach = match connectivity {
+            Connectivity::Four => 0,
+            Connectivity::Eight => 1,
+        };
+
+        // First pass: runs of foreground pixels of every row with provisional labels, merging
+        // the labels of the runs they touch on the previous row in a union-find
+        let mut runs: Vec<Run> = Vec::new();
+        let mut row_starts: Vec<usize> = Vec::with_capacity(height + 1);
+        let mut parents: Vec<u32> = vec![0];
+        for y in 0..height {
+            let previous = row_starts.last().copied().unwrap_or(0)..runs.len();
+            row_starts.push(runs.len());
+
+            let mut cracks = RowCracks::new(rows.row(y));
+            let mut first = previous.start;
+            while let (Some(start), Some(end)) = (cracks.next(), cracks.next()) {
+                // Runs of the previous row ending before this one cannot touch the next ones
+                while first < previous.end && runs[first].end + reach <= start {
+                    first += 1;
+                }
+
+                let mut label = 0;
+                let mut last = first;
+                while last < previous.end && runs[last].start < end + reach {
+                    let neighbour = runs[last].label;
+                    if label == 0 {
+                        label = neighbour;
+                    } else {
+                        union(&mut parents, label, neighbour);
+                    }
+                    last += 1;
+                }
+                if label == 0 {
+                    label = parents.len() as u32;
+                    parents.push(label);
+                }
+
+                // The last run touched may also touch the next run of this row
+                first = first.max(last.saturating_sub(1));
+                runs.push(Run { start, end, label });
+            }
+        }
+        row_starts.push(runs.len());
+
+        // Final labels, numbered in scan order
+        let mut labels = vec![0u32; width * height];
+        let mut final_labels = vec![0u32; parents.len()];
+        let mut stats: Vec<ComponentStats> = Vec::new();
+        let mut sums: Vec<(f64, f64)> = Vec::new();
+        for y in 0..height {
+            for run in &runs[row_starts[y]..row_starts[y + 1]] {
+                let root = find(&mut parents, run.label) as usize;
+                if final_labels[root] == 0 {
+                    stats.push(ComponentStats {
+                        label: stats.len() as u32 + 1,
+                        area: 0,
+                        x: run.start as u32,
+                        y: y as u32,
+                        width: 0,
+                        height: 0,
+                        centroid: (0.0, 0.0),
+                    });
+                    sums.push((0.0, 0.0));
+                    final_labels[root] = stats.len() as u32;
+                }
+                let label = final_labels[root];
+                labels[y * width + run.start..y * width + run.end].fill(label);
+
+                // Bounds are stored as right and bottom edges until the end of the scan
+                let length = run.end - run.start;
+                let component = &mut stats[label as usize - 1];
+                component.area += length;
+                component.x = component.x.min(run.start as u32);
+                component.width = component.width.max(run.end as u32);
+                component.height = component.height.max(y as u32 + 1);
+
+                // Sums of the pixel centers of the run, exact like the sums of each of them
+                let sum = &mut sums[label as usize - 1];
+                sum.0 += (length * (run.start + run.end)) as f64 / 2.0;
+                sum.1 += length as f64 * (y as f64 + 0.5);
+            }
+        }
+
+        for (component, (sum_x, sum_y)) in stats.iter_mut().zip(sums) {
+            component.width -= component.x;
+            component.height -= component.y;
+            component.centroid = (sum_x / component.area as f64, sum_y / component.area as f64);
+        }
+
+        let labels = ImageBuffer::from_raw(width as u32, height as u32, labels)
+            .expect("Label buffer matches the image dimensions");
+        Components { labels, stats }
+    }
+
+    /// Keep the `count` largest components of the foreground.
+    ///
+    /// Components of equal area are ranked in scan order.
+    pub fn keep_largest_components(&self, count: usize, connectivity: Connectivity) -> BinaryImage {
+        let components = self.label_components(connectivity);
+        let mut ranked: Vec<&ComponentStats> = components.stats().iter().collect();
+        ranked.sort_by_key(|component| std::cmp::Reverse(component.area));
+
+        let mut kept = vec![false; components.len() + 1];
+        for component in ranked.into_iter().take(count) {
+            kept[component.label as usize] = true;
+        }
+        components.select(|component| kept[component.label as usize])
+    }
+
+    /// Remove the components of the foreground with less than `min_area` pixels.
+    pub fn remove_small_components(&self, min_area: usize, connectivity: Connectivity) -> BinaryImage {
+        self.label_components(connectivity)
+            .select(|component| component.area >= min_area)
+    }
+
+    /// Fill the holes of the foreground, i.e. the background regions that do not touch the
+    /// image border, with at most `max_area` pixels, or all of them if `max_area` is `None`.
+    ///
+    /// `connectivity` is the one of the foreground, holes take the dual connectivity so that
+    /// they are the same as the holes of the polygons traced with it.
+    pub fn fill_holes(&self, max_area: Option<usize>, connectivity: Connectivity) -> BinaryImage {
+        let (width, height) = (self.width(), self.height());
+        let holes = self.invert().label_components(connectivity.dual());
+        let filled: Vec<bool> = std::iter::once(false)
+            .chain(holes.stats().iter().map(|hole| {
+                let inside = hole.x > 0 && hole.y > 0 &&
+                    hole.x + hole.width < width && hole.y + hole.height < height;
+                inside && max_area.is_none_or(|max_area| hole.area <= max_area)
+            }))
+            .collect();
+
+        let buffer: BitVec = self.bits().iter()
+            .zip(holes.labels().iter())
+            .map(|(pixel, &label)| pixel || filled[label as usize])
+            .collect();
+        BinaryImage::from_bitvec(width, height, buffer)
+    }
+}
+
+/// Horizontal run of foreground pixels `start..end` of a row, with its provisional label
+struct Run {
+    start: usize,
+    end: usize,
+    label: u32,
+}
+
+fn find(parents: &mut [u32], label: u32) -> u32 {
+    let mut root = label;
+    while parents[root as usize] != root {
+        root = parents[root as usize];
+    }
+
+    // Path compression
+    let mut current = label;
+    while parents[current as usize] != root {
+        let next = parents[current as usize];
+        parents[current as usize] = root;
+        current = next;
+    }
+    root
+}
+
+fn union(parents: &mut [u32], a: u32, b: u32) {
+    let (a, b) = (find(parents, a), find(parents, b));
+    if a != b {
+        // Keep the smallest label as the root
+        parents[a.max(b) as usize] = a.min(b);
+    }
+}
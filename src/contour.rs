@@ -1,312 +1,395 @@
 use crate::binary_image::BinaryImage;
+use crate::bit_rows::{BitRows, RowCracks};
+use crate::components::Connectivity;
 use geo::{Polygon, LineString, Coord, BoundingRect};
 
-const O_VERTEX_WITH_BORDER: [(i8, i8); 7] = [(-1, 0), (0, 0), (-1, -1), (0, 0), (0, -1), (0, 0), (0, 0)]; // Bottom left coordinates with a border
-const H_VERTEX_WITH_BORDER: [(i8, i8); 7] = [(0, 0), (0, 0), (-1, 0), (0, 0), (-1, -1), (0, 0), (0, -1)]; // Bottom right coordinates with a border
-const O_VALUE_FOR_SIGNED:   [i8; 7]       = [1, 0, 2, 0, 4, 0, 8];     // Value to add into an array of contours (using signed integers)
-const H_VALUE_FOR_SIGNED:   [i8; 7]       = [-4, 0, -8, 0, -1, 0, -2]; // (idem)
-const MN: [(i8, i8); 8] = [(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)]; // Moore neighborhood
+/// Directions of the tracer, as steps between pixel corners
+const EAST: (i64, i64) = (1, 0);
+const SOUTH: (i64, i64) = (0, 1);
+
+/// Filters applied when tracing polygons, see [`BinaryImage::trace_polygons_with`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceOptions {
+    /// Connectivity of the foreground, deciding whether pixels touching diagonally belong to
+    /// one polygon or two. The background takes the other connectivity, so that a diagonal
+    /// gap between two holes, or between a hole and the outside, merges them with
+    /// [`Connectivity::Four`] and separates them with [`Connectivity::Eight`].
+    pub connectivity: Connectivity,
+    /// Minimum width and height of the bounding box of the exterior rings, in pixels
+    pub min_dimension: usize,
+    /// Minimum number of pixels of the connected components traced, the smaller ones are
+    /// ignored along with their holes
+    pub min_area: usize,
+    /// Minimum width and height of the bounding box of the holes, in pixels
+    pub min_hole_dimension: usize,
+    /// Minimum number of pixels of the holes traced, the smaller ones are filled
+    pub min_hole_area: usize,
+}
+
+impl Default for TraceOptions {
+    fn default() -> Self {
+        TraceOptions {
+            connectivity: Connectivity::Four,
+            min_dimension: 0,
+            min_area: 0,
+            min_hole_dimension: 0,
+            min_hole_area: 0,
+        }
+    }
+}
+
+/// Ring of a [`ContourTree`]
+#[derive(Debug, Clone)]
+pub struct ContourNode {
+    ring: LineString,
+    hole: bool,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    depth: usize,
+}
+
+impl ContourNode {
+    /// Closed ring, in pixel corner coordinates
+    #[inline]
+    #[must_use]
+    pub fn ring(&self) -> &LineString {
+        &self.ring
+    }
+
+    /// Whether the ring bounds a hole rather than the exterior of a polygon
+    #[inline]
+    #[must_use]
+    pub fn is_hole(&self) -> bool {
+        self.hole
+    }
+
+    /// Index of the innermost ring enclosing this one
+    #[inline]
+    #[must_use]
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    /// Indices of the rings directly enclosed by this one, in scan order
+    #[inline]
+    #[must_use]
+    pub fn children(&self) -> &[usize] {
+        &self.children
+    }
+
+    /// Number of rings enclosing this one: 0 for the outermost exteriors, 1 for their holes,
+    /// 2 for the islands inside those holes, and so on
+    #[inline]
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
+/// Nesting of the rings of the foreground, see [`BinaryImage::trace_hierarchy`]
+///
+/// Exteriors and holes alternate along every branch of the tree: the children of an exterior
+/// are its holes, and the children of a hole are the islands inside of it.
+#[derive(Debug, Clone, Default)]
+pub struct ContourTree {
+    nodes: Vec<ContourNode>,
+}
+
+impl ContourTree {
+    /// Rings in the order they were met when scanning the image row by row, parents coming
+    /// before their children
+    #[inline]
+    #[must_use]
+    pub fn nodes(&self) -> &[ContourNode] {
+        &self.nodes
+    }
+
+    /// Indices of the outermost exteriors
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        self.nodes.iter()
+            .enumerate()
+            .filter(|(_, node)| node.parent.is_none())
+            .map(|(i, _)| i)
+    }
+
+    /// Number of rings
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Polygons made of every exterior and its holes, in scan order.
+    ///
+    /// Islands inside of holes are polygons of their own.
+    pub fn to_polygons(&self) -> Vec<Polygon> {
+        self.nodes.iter()
+            .filter(|node| !node.hole)
+            .map(|node| {
+                let holes = node.children.iter()
+                    .map(|&child| self.nodes[child].ring.clone())
+                    .collect();
+                Polygon::new(node.ring.clone(), holes)
+            })
+            .collect()
+    }
+}
+
+/// Ring traced before being added to the tree, along with whether it passed the filters
+struct TracedRing {
+    ring: LineString,
+    hole: bool,
+    parent: Option<usize>,
+    kept: bool,
+}
 
 impl BinaryImage {
 
     pub fn trace_polygons(&self, min_dimension: usize) -> Vec<Polygon> {
-        let width = self.width() as usize;
-        let height = self.height() as usize;
-        let mut contours = vec![vec![0i8; width + 2]; height + 2];
-        let mut polygons = Vec::new();
-        let mut inner_rings = Vec::new();
-
-        // Initialize contours array with proper values
-        for y in 0..height {
-            for x in 0..width {
-                let pixel = self.get_pixel(x as u32, y as u32);
-                contours[y + 1][x + 1] = if *pixel { 1 } else { -1 };
-            }
-        }
+        self.trace_polygons_with(&TraceOptions {
+            min_dimension,
+            min_hole_dimension: min_dimension,
+            ..Default::default()
+        })
+    }
 
-        let mut ol;
-        let mut hl;
-
-        for y in 1..=height {
-            ol = 0;
-            hl = 0;
-            for x in 1..=width {
-                // Check for exterior boundary (foreground pixel with background to the left)
-                if ol == hl && contours[y][x] == 1 {
-                    // Verify this is actually a boundary pixel by checking if there's a background pixel adjacent
-                    let is_boundary = contours[y][x-1] <= 0 || contours[y-1][x] <= 0 ||
-                        contours[y+1][x] <= 0 || contours[y][x+1] <= 0;
-
-                    if is_boundary {
-                        let ring = self.trace_polygon(
-                            true,
-                            x,
-                            y,
-                            [2, 3, 4, 5, 6, 7, 0, 1],
-                            2,
-                            (7, 1, 0),
-                            O_VERTEX_WITH_BORDER,
-                            O_VALUE_FOR_SIGNED,
-                            &mut contours,
-                        );
-                        if min_dimension == 0 || ring.bounding_rect()
-                            .map(|rect| rect.width() >= min_dimension as f64 && rect.height() >= min_dimension as f64)
-                            .unwrap_or(false)
-                        {
-                            polygons.push(Polygon::new(ring, vec![]));
-                        }
-                    }
-                }
-                // Check for interior boundary (background pixel with foreground to the left, indicating a hole)
-                else if ol > hl && contours[y][x] == -1 {
-                    // Verify this is actually an interior boundary by checking adjacent pixels
-                    let is_interior_boundary = contours[y][x-1] > 0 || contours[y-1][x] > 0 ||
-                        contours[y+1][x] > 0 || contours[y][x+1] > 0;
-
-                    if is_interior_boundary {
-                        let ring = self.trace_polygon(
-                            false,
-                            x,
-                            y,
-                            [4, 5, 6, 7, 0, 1, 2, 3],
-                            -2,
-                            (1, 7, 6),
-                            H_VERTEX_WITH_BORDER,
-                            H_VALUE_FOR_SIGNED,
-                            &mut contours,
-                        );
-                        if min_dimension == 0 || ring.bounding_rect()
-                            .map(|rect| rect.width() >= min_dimension as f64 && rect.height() >= min_dimension as f64)
-                            .unwrap_or(false)
-                        {
-                            inner_rings.push(ring);
-                        }
-                    }
-                }
+    /// Trace the polygons of the foreground, skipping the rings filtered out by `options`.
+    pub fn trace_polygons_with(&self, options: &TraceOptions) -> Vec<Polygon> {
+        self.trace_hierarchy(options).to_polygons()
+    }
+
+    /// Trace the rings of the foreground along with their nesting, skipping the rings
+    /// filtered out by `options` and the rings they enclose.
+    ///
+    /// The parent of every ring is known as soon as it is met, from the borders crossed
+    /// before it on its row.
+    pub fn trace_hierarchy(&self, options: &TraceOptions) -> ContourTree {
+        self.without_small_regions(options)
+            .as_ref()
+            .unwrap_or(self)
+            .trace(options)
+    }
+
+    /// Fill the holes and remove the components smaller than the area filters of `options`,
+    /// `None` if they keep every pixel.
+    pub(crate) fn without_small_regions(&self, options: &TraceOptions) -> Option<BinaryImage> {
+        let mut filtered = None;
+        if options.min_hole_area > 1 {
+            filtered = Some(self.fill_holes(Some(options.min_hole_area - 1), options.connectivity));
+        }
+        if options.min_area > 0 {
+            let image = filtered.as_ref().unwrap_or(self);
+            filtered = Some(image.remove_small_components(options.min_area, options.connectivity));
+        }
+        filtered
+    }
 
-                // Update ol and hl counters based on contour values
-                let abs_val = contours[y][x].abs();
-                match abs_val {
-                    2 | 4 | 10 | 12 => {
-                        if contours[y][x] > 0 {
-                            ol += 1
+    /// Follow the cracks between foreground and background pixels.
+    ///
+    /// Rings run along pixel corners with the foreground on their left, in a frame where y
+    /// points up: exteriors turn counterclockwise and holes clockwise. Every ring starts on
+    /// the top left corner of the first pixel of the region it bounds in scan order, and is
+    /// traced as soon as the scan crosses one of its vertical cracks for the first time.
+    pub(crate) fn trace(&self, options: &TraceOptions) -> ContourTree {
+        let rows = BitRows::from_image(self);
+        let mut tracer = CrackTracer::new(&rows, options.connectivity);
+        let mut rings: Vec<TracedRing> = Vec::new();
+
+        // Rings enclosing the current pixel of the scan, innermost last
+        let mut open: Vec<usize> = Vec::new();
+
+        for y in 0..rows.height() {
+            open.clear();
+            for x in RowCracks::new(rows.row(y)) {
+                match tracer.ring(x, y) {
+                    None => {
+                        // The first crack met is on the left of the region bounded by the ring
+                        let hole = tracer.foreground(x as i64 - 1, y as i64);
+                        let id = rings.len();
+                        let (direction, min_dimension) = if hole {
+                            (SOUTH, options.min_hole_dimension)
                         } else {
-                            hl += 1
-                        }
+                            (EAST, options.min_dimension)
+                        };
+                        let traced = tracer.trace_ring(id, (x as i64, y as i64), direction);
+                        rings.push(TracedRing::new(traced, hole, open.last().copied(), min_dimension, &rings));
+                        open.push(id);
                     }
-                    5 | 7 | 13 | 15 => {
-                        if contours[y][x] > 0 {
-                            ol -= 1
-                        } else {
-                            hl -= 1
-                        }
+                    Some(ring) if open.last() == Some(&ring) => {
+                        open.pop();
                     }
-                    _ => (),
+                    Some(ring) => open.push(ring),
                 }
             }
         }
 
-        // Assign interior rings to their containing polygons
-        for inner_ring in inner_rings {
-            // Find the smallest polygon that contains this inner ring
-            let mut best_polygon_idx = None;
-            let mut best_area = f64::INFINITY;
-
-            for (idx, polygon) in polygons.iter().enumerate() {
-                if self.polygon_contains_ring(polygon.exterior(), &inner_ring) {
-                    let area = self.calculate_polygon_area(polygon.exterior());
-                    if area < best_area {
-                        best_area = area;
-                        best_polygon_idx = Some(idx);
-                    }
-                }
-            }
+        ContourTree::from_rings(rings)
+    }
+}
 
-            if let Some(idx) = best_polygon_idx {
-                polygons[idx].interiors_push(inner_ring);
+/// Ring of the cracks not traced yet
+const UNTRACED: u32 = u32::MAX;
+
+/// Pixels of the image along with the ring of every vertical crack.
+///
+/// Pixels are read from the rows, and rings are only stored for the cracks, numbered in
+/// scan order. `ranks` holds for every row the number of the first crack of each of its
+/// words, plus one past its last word for the crack on the right of a row ending on a
+/// full word.
+struct CrackTracer<'a> {
+    rows: &'a BitRows,
+    ranks: Vec<u32>,
+    rings: Vec<u32>,
+    connectivity: Connectivity,
+}
+
+impl<'a> CrackTracer<'a> {
+    fn new(rows: &'a BitRows, connectivity: Connectivity) -> Self {
+        let mut ranks = Vec::with_capacity((rows.words_per_row() + 1) * rows.height());
+        let mut count = 0usize;
+        for y in 0..rows.height() {
+            for j in 0..=rows.words_per_row() {
+                ranks.push(u32::try_from(count).expect("too many cracks to trace"));
+                count += Self::cracks(rows.row(y), j).count_ones() as usize;
             }
         }
 
-        polygons
+        CrackTracer { rows, ranks, rings: vec![UNTRACED; count], connectivity }
     }
 
-    fn trace_polygon(
-        &self,
-        outline: bool,
-        cursor_x: usize,
-        cursor_y: usize,
-        mut o: [usize; 8],
-        rot: i8,
-        viv: (usize, usize, usize),
-        vertex: [(i8, i8); 7],
-        value: [i8; 7],
-        contours: &mut Vec<Vec<i8>>,
-    ) -> LineString {
-        let mut tracer_x = cursor_x;
-        let mut tracer_y = cursor_y;
-        let mut vertices_nbr: usize = 1;
-        let mut ring = Vec::<Coord>::new();
-
-        ring.push(Coord {
-            x: (tracer_x as f64) + (vertex[o[0]].0 as f64),
-            y: (tracer_y as f64) + (vertex[o[0]].1 as f64),
-        });
-
-        let mut neighbors: [i8; 8];
-        let mut rn: u8;
+    /// Cracks of word `j` of a row, bit `x % 64` being set for crack `x`.
+    #[inline]
+    fn cracks(row: &[u64], j: usize) -> u64 {
+        let word = row.get(j).copied().unwrap_or(0);
+        let carry = match j {
+            0 => 0,
+            _ => row[j - 1] >> 63,
+        };
+        word ^ ((word << 1) | carry)
+    }
 
-        loop {
-            // Safely access neighbors with bounds checking
-            neighbors = [
-                if tracer_y > 0 { contours[tracer_y - 1][tracer_x] } else { 0 },                                    // 0
-                if tracer_y > 0 && tracer_x < contours[0].len() - 1 { contours[tracer_y - 1][tracer_x + 1] } else { 0 }, // 1
-                if tracer_x < contours[0].len() - 1 { contours[tracer_y][tracer_x + 1] } else { 0 },               // 2
-                if tracer_y < contours.len() - 1 && tracer_x < contours[0].len() - 1 { contours[tracer_y + 1][tracer_x + 1] } else { 0 }, // 3
-                if tracer_y < contours.len() - 1 { contours[tracer_y + 1][tracer_x] } else { 0 },                  // 4
-                if tracer_y < contours.len() - 1 && tracer_x > 0 { contours[tracer_y + 1][tracer_x - 1] } else { 0 }, // 5
-                if tracer_x > 0 { contours[tracer_y][tracer_x - 1] } else { 0 },                                  // 6
-                if tracer_y > 0 && tracer_x > 0 { contours[tracer_y - 1][tracer_x - 1] } else { 0 },             // 7
-            ];
-
-            rn = if outline {
-                if neighbors[o[7]] > 0 && neighbors[o[0]] > 0 {
-                    1
-                } else if neighbors[o[0]] > 0 {
-                    2
-                } else if neighbors[o[1]] > 0 && neighbors[o[2]] > 0 {
-                    3
-                } else {
-                    0
-                }
-            } else if neighbors[o[1]] < 0 && neighbors[o[0]] < 0 {
-                1
-            } else if neighbors[o[0]] < 0 {
-                2
-            } else if neighbors[o[7]] < 0 && neighbors[o[6]] < 0 {
-                3
-            } else {
-                0
-            };
+    /// Index of the vertical crack on the left of pixel `(x, y)`, which must be a crack.
+    #[inline]
+    fn index(&self, x: usize, y: usize) -> usize {
+        let (j, bit) = (x / 64, x % 64);
+        let before = Self::cracks(self.rows.row(y), j) & ((1 << bit) - 1);
+        self.ranks[y * (self.rows.words_per_row() + 1) + j] as usize + before.count_ones() as usize
+    }
 
-            match rn {
-                1 => {
-                    contours[tracer_y][tracer_x] += value[o[0]];
-                    tracer_x = tracer_x.wrapping_add(MN[o[viv.0]].0 as usize);
-                    tracer_y = tracer_y.wrapping_add(MN[o[viv.0]].1 as usize);
-                    // Rotate 90 degrees, counterclockwise for the outlines (rot = 2) or clockwise for the holes (rot = -2)
-                    o.rotate_right(rot.rem_euclid(8) as usize);
-                    vertices_nbr += 1;
-                }
-                2 => {
-                    contours[tracer_y][tracer_x] += value[o[0]];
-                    tracer_x = tracer_x.wrapping_add(MN[o[0]].0 as usize);
-                    tracer_y = tracer_y.wrapping_add(MN[o[0]].1 as usize);
-                }
-                3 => {
-                    contours[tracer_y][tracer_x] += value[o[0]];
-                    // Rotate 90 degrees, clockwise for the outlines (rot = 2) or counterclockwise for the holes (rot = -2)
-                    o.rotate_left(rot.rem_euclid(8) as usize);
-                    contours[tracer_y][tracer_x] += value[o[0]];
-                    vertices_nbr += 1;
-                    ring.push(Coord {
-                        x: (tracer_x as f64) + (vertex[o[0]].0 as f64),
-                        y: (tracer_y as f64) + (vertex[o[0]].1 as f64),
-                    });
-                    o.rotate_right(rot.rem_euclid(8) as usize);
-                    tracer_x = tracer_x.wrapping_add(MN[o[viv.1]].0 as usize);
-                    tracer_y = tracer_y.wrapping_add(MN[o[viv.1]].1 as usize);
-                    vertices_nbr += 1;
-                }
-                _ => {
-                    contours[tracer_y][tracer_x] += value[o[0]];
-                    o.rotate_left(rot.rem_euclid(8) as usize);
-                    vertices_nbr += 1;
-                },
-            }
+    /// Whether a pixel is foreground, pixels outside of the image being background.
+    #[inline]
+    fn foreground(&self, x: i64, y: i64) -> bool {
+        if x < 0 || y < 0 || x as usize >= self.rows.width() || y as usize >= self.rows.height() {
+            return false;
+        }
+        let (x, y) = (x as usize, y as usize);
+        self.rows.row(y)[x / 64] >> (x % 64) & 1 != 0
+    }
 
-            // Check if we've returned to the starting position
-            if tracer_x == cursor_x && tracer_y == cursor_y && vertices_nbr > 2 {
-                break;
-            }
+    /// Ring of the crack on the left of pixel `(x, y)`, if it was traced.
+    #[inline]
+    fn ring(&self, x: usize, y: usize) -> Option<usize> {
+        let ring = self.rings[self.index(x, y)];
+        (ring != UNTRACED).then_some(ring as usize)
+    }
 
-            if rn != 2 {
-                ring.push(Coord {
-                    x: (tracer_x as f64) + (vertex[o[0]].0 as f64),
-                    y: (tracer_y as f64) + (vertex[o[0]].1 as f64),
-                });
-            }
-        }
+    #[inline]
+    fn set_ring(&mut self, x: i64, y: i64, id: u32) {
+        let index = self.index(x as usize, y as usize);
+        self.rings[index] = id;
+    }
+
+    /// Trace the ring leaving the pixel corner `start` in `direction`, recording it as the
+    /// ring of the vertical cracks it follows.
+    fn trace_ring(&mut self, id: usize, start: (i64, i64), direction: (i64, i64)) -> LineString {
+        assert!(id < UNTRACED as usize, "too many rings to trace");
+        let id = id as u32;
+        let (mut x, mut y) = start;
+        let (mut dx, mut dy) = direction;
+        let mut ring = vec![Coord { x: x as f64, y: y as f64 }];
 
-        // Final cleanup loop
         loop {
-            contours[tracer_y][tracer_x] += value[o[0]];
-            if o[0] == viv.2 {
+            match (dx, dy) {
+                (0, 1) => self.set_ring(x, y, id),
+                (0, -1) => self.set_ring(x, y - 1, id),
+                _ => (),
+            }
+            x += dx;
+            y += dy;
+
+            // Pixels ahead of the corner, on the foreground side and on the background side
+            let (sx, sy) = (-dy, dx);
+            let ahead_foreground = self.foreground(x + (dx + sx - 1) / 2, y + (dy + sy - 1) / 2);
+            let ahead_background = self.foreground(x + (dx - sx - 1) / 2, y + (dy - sy - 1) / 2);
+
+            // Turn away from the foreground when it continues ahead on the background side,
+            // which joins diagonal pixels only when they are 8-connected
+            let turn = if ahead_background && (ahead_foreground || self.connectivity == Connectivity::Eight) {
+                (-sx, -sy)
+            } else if ahead_foreground {
+                (dx, dy)
+            } else {
+                (sx, sy)
+            };
+
+            if (x, y) == start && turn == direction {
                 break;
             }
-            o.rotate_left(rot.rem_euclid(8) as usize);
-            vertices_nbr += 1;
-            ring.push(Coord {
-                x: (tracer_x as f64) + (vertex[o[0]].0 as f64),
-                y: (tracer_y as f64) + (vertex[o[0]].1 as f64),
-            });
+            if turn != (dx, dy) {
+                ring.push(Coord { x: x as f64, y: y as f64 });
+            }
+            (dx, dy) = turn;
         }
 
         LineString::from(ring)
     }
+}
 
-    // Helper function to check if a polygon contains a ring
-    fn polygon_contains_ring(&self, exterior: &LineString, inner_ring: &LineString) -> bool {
-        // Simple point-in-polygon test using the first point of the inner ring
-        if let Some(test_point) = inner_ring.coords().next() {
-            self.point_in_polygon(test_point, exterior)
-        } else {
-            false
-        }
+impl TracedRing {
+    fn new(mut ring: LineString, hole: bool, parent: Option<usize>, min_dimension: usize, rings: &[TracedRing]) -> Self {
+        ring.close();
+        let large_enough = min_dimension == 0 || ring.bounding_rect()
+            .map(|rect| rect.width() >= min_dimension as f64 && rect.height() >= min_dimension as f64)
+            .unwrap_or(false);
+        let kept = large_enough && parent.is_none_or(|parent| rings[parent].kept);
+        TracedRing { ring, hole, parent, kept }
     }
+}
 
-    // Helper function for point-in-polygon test using ray casting
-    fn point_in_polygon(&self, point: &Coord, polygon: &LineString) -> bool {
-        let mut inside = false;
-        let coords: Vec<&Coord> = polygon.coords().collect();
-        let n = coords.len();
-
-        if n < 3 {
-            return false;
-        }
-
-        let mut j = n - 1;
-        for i in 0..n {
-            let xi = coords[i].x;
-            let yi = coords[i].y;
-            let xj = coords[j].x;
-            let yj = coords[j].y;
-
-            if ((yi > point.y) != (yj > point.y)) &&
-                (point.x < (xj - xi) * (point.y - yi) / (yj - yi) + xi) {
-                inside = !inside;
+impl ContourTree {
+    /// Tree of the rings that were kept, in scan order.
+    fn from_rings(rings: Vec<TracedRing>) -> Self {
+        let mut indices = vec![usize::MAX; rings.len()];
+        let mut nodes: Vec<ContourNode> = Vec::new();
+        for (i, ring) in rings.into_iter().enumerate() {
+            if !ring.kept {
+                continue;
             }
-            j = i;
-        }
-        inside
-    }
-
-    // Helper function to calculate polygon area (for finding the smallest containing polygon)
-    fn calculate_polygon_area(&self, polygon: &LineString) -> f64 {
-        let coords: Vec<&Coord> = polygon.coords().collect();
-        let n = coords.len();
-
-        if n < 3 {
-            return 0.0;
-        }
-
-        let mut area = 0.0;
-        let mut j = n - 1;
 
-        for i in 0..n {
-            area += (coords[j].x + coords[i].x) * (coords[j].y - coords[i].y);
-            j = i;
+            let index = nodes.len();
+            indices[i] = index;
+            let parent = ring.parent.map(|parent| indices[parent]);
+            let depth = match parent {
+                Some(parent) => {
+                    nodes[parent].children.push(index);
+                    nodes[parent].depth + 1
+                }
+                None => 0,
+            };
+            nodes.push(ContourNode {
+                ring: ring.ring,
+                hole: ring.hole,
+                parent,
+                children: Vec::new(),
+                depth,
+            });
         }
-
-        area.abs() / 2.0
+        ContourTree { nodes }
     }
 }
@@ -0,0 +1,193 @@
+use image::{GrayImage, ImageBuffer, Luma, Primitive};
+use crate::binary_image::BinaryImage;
+
+/// Squared distance standing for "no other pixel found yet"
+const INF: f64 = 1e20;
+
+/// Signed distance field of a [`BinaryImage`], see [`BinaryImage::signed_distance_field`].
+#[derive(Debug, Clone)]
+pub struct SignedDistanceField {
+    spread: f32,
+    distances: ImageBuffer<Luma<f32>, Vec<f32>>,
+}
+
+impl SignedDistanceField {
+    /// Distance to the foreground edge clamped to `[-spread, spread]`, in pixels
+    #[inline]
+    #[must_use]
+    pub fn spread(&self) -> f32 {
+        self.spread
+    }
+
+    /// Signed distances in pixels, positive inside the foreground
+    #[inline]
+    #[must_use]
+    pub fn distances(&self) -> &ImageBuffer<Luma<f32>, Vec<f32>> {
+        &self.distances
+    }
+
+    /// Encode the field as an 8-bit image, the edge is at 128.
+    pub fn to_luma8(&self) -> GrayImage {
+        self.encode(u8::MAX as f32)
+    }
+
+    /// Encode the field as a 16-bit image, the edge is at 32768.
+    pub fn to_luma16(&self) -> ImageBuffer<Luma<u16>, Vec<u16>> {
+        self.encode(u16::MAX as f32)
+    }
+
+    /// Map `[-spread, spread]` to `[0, max]`.
+    fn encode<T: Primitive>(&self, max: f32) -> ImageBuffer<Luma<T>, Vec<T>> {
+        let (width, height) = self.distances.dimensions();
+        ImageBuffer::from_fn(width, height, |x, y| {
+            let distance = self.distances.get_pixel(x, y).0[0];
+            let value = (0.5 + distance / (2.0 * self.spread)).clamp(0.0, 1.0);
+            Luma([T::from((value * max).round()).unwrap_or_else(T::zero)])
+        })
+    }
+}
+
+impl BinaryImage {
+    /// Exact Euclidean distance transform of the foreground.
+    ///
+    /// Every foreground pixel holds the distance between its center and the center of the
+    /// nearest background pixel, background pixels hold 0. Pixels outside of the image are
+    /// considered background, so a foreground pixel on the image border is at distance 1.
+    ///
+    /// Uses the separable algorithm of Felzenszwalb and Huttenlocher, "Distance Transforms of
+    /// Sampled Functions", which runs in linear time.
+    pub fn distance_transform(&self) -> ImageBuffer<Luma<f32>, Vec<f32>> {
+        let squared_distances = self.squared_distances(true);
+        ImageBuffer::from_fn(self.width(), self.height(), |x, y| {
+            Luma([squared_distances[(y * self.width() + x) as usize].sqrt() as f32])
+        })
+    }
+
+    /// Signed distance between every pixel and the edge of the foreground, positive inside.
+    ///
+    /// The edge runs between pixel centers, so pixels touching it are at a distance of half
+    /// a pixel. Distances are clamped to `[-spread, spread]`, which is also the range mapped
+    /// to the full range of the 8 and 16-bit encodings of the field.
+    pub fn signed_distance_field(&self, spread: f32) -> SignedDistanceField {
+        let inside = self.squared_distances(true);
+        let outside = self.squared_distances(false);
+        let spread = spread.max(f32::EPSILON);
+
+        let distances = ImageBuffer::from_fn(self.width(), self.height(), |x, y| {
+            let i = (y * self.width() + x) as usize;
+            let distance = if *self.get_pixel(x, y) {
+                inside[i].sqrt() - 0.5
+            } else {
+                0.5 - outside[i].sqrt()
+            };
+            Luma([(distance as f32).clamp(-spread, spread)])
+        });
+
+        SignedDistanceField { spread, distances }
+    }
+
+    /// Squared distances between the pixels equal to `foreground` and the nearest pixel that
+    /// is not, in row major order. Pixels outside of the image are background.
+    fn squared_distances(&self, foreground: bool) -> Vec<f64> {
+        let width = self.width() as usize;
+        let height = self.height() as usize;
+
+        // One pixel of padding on each side
+        let padded_width = width + 2;
+        let padded_height = height + 2;
+        let padding = if foreground { 0.0 } else { INF };
+        let mut grid = vec![padding; padded_width * padded_height];
+        for y in 0..height {
+            for x in 0..width {
+                grid[(y + 1) * padded_width + x + 1] = if *self.get_pixel(x as u32, y as u32) == foreground {
+                    INF
+                } else {
+                    0.0
+                };
+            }
+        }
+
+        let mut buffer = Buffers::new(padded_width.max(padded_height));
+
+        // Transform along columns, then along rows
+        let mut column = vec![0f64; padded_height];
+        for x in 0..padded_width {
+            for y in 0..padded_height {
+                column[y] = grid[y * padded_width + x];
+            }
+            buffer.transform(&mut column);
+            for y in 0..padded_height {
+                grid[y * padded_width + x] = column[y];
+            }
+        }
+        for row in grid.chunks_mut(padded_width) {
+            buffer.transform(row);
+        }
+
+        grid.chunks(padded_width)
+            .skip(1)
+            .take(height)
+            .flat_map(|row| row[1..=width].iter().copied())
+            .collect()
+    }
+}
+
+/// Scratch buffers of the one dimensional transform.
+struct Buffers {
+    /// Locations of the parabolas of the lower envelope
+    parabolas: Vec<usize>,
+    /// Boundaries between consecutive parabolas of the lower envelope
+    boundaries: Vec<f64>,
+    /// Copy of the sampled function
+    values: Vec<f64>,
+}
+
+impl Buffers {
+    fn new(len: usize) -> Self {
+        Buffers {
+            parabolas: vec![0; len],
+            boundaries: vec![0.0; len + 1],
+            values: vec![0.0; len],
+        }
+    }
+
+    /// Replace `f` by its one dimensional squared distance transform, i.e. the lower envelope
+    /// of the parabolas `(q - p)^2 + f(p)`.
+    fn transform(&mut self, f: &mut [f64]) {
+        let n = f.len();
+        let values = &mut self.values[..n];
+        values.copy_from_slice(f);
+
+        let v = &mut self.parabolas;
+        let z = &mut self.boundaries;
+        let mut k = 0;
+        v[0] = 0;
+        z[0] = f64::NEG_INFINITY;
+        z[1] = f64::INFINITY;
+
+        for q in 1..n {
+            let intersection = |p: usize| {
+                ((values[q] + (q * q) as f64) - (values[p] + (p * p) as f64)) / (2 * (q - p)) as f64
+            };
+            let mut s = intersection(v[k]);
+            while s <= z[k] {
+                k -= 1;
+                s = intersection(v[k]);
+            }
+            k += 1;
+            v[k] = q;
+            z[k] = s;
+            z[k + 1] = f64::INFINITY;
+        }
+
+        k = 0;
+        for (q, value) in f.iter_mut().enumerate() {
+            while z[k + 1] < q as f64 {
+                k += 1;
+            }
+            let p = v[k];
+            let d = q as f64 - p as f64;
+            *value = d * d + values[p];
+        }
+    }
+}
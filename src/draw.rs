@@ -9,17 +9,22 @@ pub trait DrawMesh {
 
 /// Convert a geo::Polygon LineString into Vec<Point<i32>> suitable for imageproc
 fn linestring_to_points(linestring: &geo::LineString) -> Vec<Point<i32>> {
-    let points: Vec<Point<i32>> = linestring
+    let mut points: Vec<Point<i32>> = linestring
         .points()
         .map(|p| Point::new(p.x() as i32, p.y() as i32))
         .collect();
 
-    // Remove duplicate last point if it matches the first (closed polygon)
-    if points.len() >= 2 && points.first() == points.last() {
-        points[..points.len() - 1].to_vec()
-    } else {
-        points
+    // Sub-pixel vertices may land on the same pixel, which imageproc does not accept
+    points.dedup();
+
+    // Remove duplicate last points if they match the first (closed polygon)
+    while points.len() >= 2 && points.first() == points.last() {
+        points.pop();
+    }
+    if points.len() < 2 {
+        points.clear();
     }
+    points
 }
 
 impl DrawMesh for Polygon {
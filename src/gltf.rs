@@ -0,0 +1,418 @@
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use anyhow::anyhow;
+use crate::mesh::{face_normal, Mesh3D};
+
+const GLB_MAGIC: u32 = 0x4654_6C67; // "glTF"
+const GLB_VERSION: u32 = 2;
+const GLB_CHUNK_JSON: u32 = 0x4E4F_534A; // "JSON"
+const GLB_CHUNK_BIN: u32 = 0x004E_4942; // "BIN\0"
+
+const GL_ARRAY_BUFFER: u32 = 34962;
+const GL_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const GL_FLOAT: u32 = 5126;
+const GL_UNSIGNED_INT: u32 = 5125;
+const GL_TRIANGLES: u32 = 4;
+
+/// Image source of a material texture
+enum GltfImage {
+    /// Relative URI to an external image file
+    Uri(String),
+    /// Image bytes stored in the binary buffer
+    Embedded { bytes: Vec<u8>, mime_type: &'static str },
+}
+
+struct BufferView {
+    offset: usize,
+    length: usize,
+    target: Option<u32>,
+}
+
+struct Accessor {
+    buffer_view: usize,
+    component_type: u32,
+    count: usize,
+    kind: &'static str,
+    min: Vec<f64>,
+    max: Vec<f64>,
+}
+
+impl Mesh3D {
+    /// Export the mesh as a glTF 2.0 document.
+    ///
+    /// Geometry is written to a `.bin` file next to the `.gltf` file and textures are referenced
+    /// from the `textures/` folder, the same way `export_obj` references them from the MTL file.
+    pub fn export_gltf(&self, gltf_path: &Path, front_texture: &str, back_texture: &str, side_texture: &str) -> anyhow::Result<()> {
+        let bin_path = gltf_path.with_extension("bin");
+        let bin_filename = bin_path.file_name()
+            .ok_or_else(|| anyhow!("glTF path {} has no file name", gltf_path.display()))?
+            .to_string_lossy()
+            .to_string();
+
+        let images = [front_texture, back_texture, side_texture]
+            .map(|texture| GltfImage::Uri(format!("textures/{}", texture)));
+
+        let (json, buffer) = self.build_gltf(images, Some(&bin_filename));
+
+        if !buffer.is_empty() {
+            fs::write(&bin_path, &buffer)?;
+        }
+        fs::write(gltf_path, json)?;
+
+        Ok(())
+    }
+
+    /// Export the mesh as a binary glTF 2.0 (GLB) file with the textures embedded.
+    ///
+    /// Textures must be PNG or JPEG images, which are the only formats supported by glTF.
+    pub fn export_glb(&self, glb_path: &Path, front_texture: &Path, back_texture: &Path, side_texture: &Path) -> anyhow::Result<()> {
+        let images = [
+            embedded_image(front_texture)?,
+            embedded_image(back_texture)?,
+            embedded_image(side_texture)?,
+        ];
+
+        let (json, buffer) = self.build_gltf(images, None);
+
+        let json_length = json.len().next_multiple_of(4);
+        let buffer_length = buffer.len().next_multiple_of(4);
+        let bin_chunk_length = if buffer.is_empty() { 0 } else { 8 + buffer_length };
+        let total_length = 12 + 8 + json_length + bin_chunk_length;
+
+        let file = File::create(glb_path)?;
+        let mut writer = BufWriter::new(file);
+
+        // Header
+        writer.write_all(&GLB_MAGIC.to_le_bytes())?;
+        writer.write_all(&GLB_VERSION.to_le_bytes())?;
+        writer.write_all(&(total_length as u32).to_le_bytes())?;
+
+        // JSON chunk, padded with spaces
+        writer.write_all(&(json_length as u32).to_le_bytes())?;
+        writer.write_all(&GLB_CHUNK_JSON.to_le_bytes())?;
+        writer.write_all(json.as_bytes())?;
+        writer.write_all(&b"   "[..json_length - json.len()])?;
+
+        // Binary chunk, padded with zeros, omitted when there is no buffer
+        if !buffer.is_empty() {
+            writer.write_all(&(buffer_length as u32).to_le_bytes())?;
+            writer.write_all(&GLB_CHUNK_BIN.to_le_bytes())?;
+            writer.write_all(&buffer)?;
+            writer.write_all(&[0u8; 3][..buffer_length - buffer.len()])?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Build the glTF JSON document and its binary buffer.
+    ///
+    /// Each face group becomes a primitive of a single mesh, with its own material whose base
+    /// color texture is the front, back or side image depending on the group name.
+    fn build_gltf(&self, images: [GltfImage; 3], buffer_uri: Option<&str>) -> (String, Vec<u8>) {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut buffer_views: Vec<BufferView> = Vec::new();
+        let mut accessors: Vec<Accessor> = Vec::new();
+
+        let normals = if self.has_normals() {
+            self.normals.clone()
+        } else {
+            self.vertex_normals()
+        };
+
+        // glTF places the texture origin at the top left corner, OBJ at the bottom left
+        let texcoords: Vec<[f64; 2]> = self.uvs.iter().map(|[u, v]| [*u, -*v]).collect();
+
+        // glTF forbids empty accessors, a mesh without faces is written as an empty scene
+        let empty = self.faces.iter().all(|group| group.indices.is_empty());
+        let (position_accessor, normal_accessor, texcoord_accessor) = if empty {
+            (0, 0, 0)
+        } else {
+            (
+                push_vertex_accessor(&mut buffer, &mut buffer_views, &mut accessors, &self.vertices, "VEC3"),
+                push_vertex_accessor(&mut buffer, &mut buffer_views, &mut accessors, &normals, "VEC3"),
+                push_vertex_accessor(&mut buffer, &mut buffer_views, &mut accessors, &texcoords, "VEC2"),
+            )
+        };
+
+        // Index accessors, one per non-empty face group
+        let mut primitives: Vec<(usize, usize)> = Vec::new();
+        for (material, group) in self.faces.iter().enumerate() {
+            if group.indices.is_empty() {
+                continue;
+            }
+
+            let offset = buffer.len();
+            let mut min = u32::MAX;
+            let mut max = 0u32;
+            for &index in group.indices.iter().flatten() {
+                let index = index as u32;
+                min = min.min(index);
+                max = max.max(index);
+                buffer.extend_from_slice(&index.to_le_bytes());
+            }
+            buffer_views.push(BufferView { offset, length: buffer.len() - offset, target: Some(GL_ELEMENT_ARRAY_BUFFER) });
+            accessors.push(Accessor {
+                buffer_view: buffer_views.len() - 1,
+                component_type: GL_UNSIGNED_INT,
+                count: group.indices.len() * 3,
+                kind: "SCALAR",
+                min: vec![min as f64],
+                max: vec![max as f64],
+            });
+            primitives.push((accessors.len() - 1, material));
+        }
+
+        // Images, deduplicated since the back and side textures default to the front texture
+        let mut image_sources: Vec<usize> = Vec::with_capacity(images.len());
+        let mut unique_images: Vec<String> = Vec::new();
+        let mut unique_keys: Vec<&GltfImage> = Vec::new();
+        for image in &images {
+            if let Some(index) = unique_keys.iter().position(|other| same_image(other, image)) {
+                image_sources.push(index);
+                continue;
+            }
+            let json = match image {
+                GltfImage::Uri(uri) => format!("{{\"uri\":\"{}\"}}", escape_json(uri)),
+                GltfImage::Embedded { bytes, mime_type } => {
+                    let offset = buffer.len();
+                    buffer.extend_from_slice(bytes);
+                    buffer_views.push(BufferView { offset, length: bytes.len(), target: None });
+                    pad_to_alignment(&mut buffer);
+                    format!("{{\"bufferView\":{},\"mimeType\":\"{}\"}}", buffer_views.len() - 1, mime_type)
+                }
+            };
+            image_sources.push(unique_images.len());
+            unique_images.push(json);
+            unique_keys.push(image);
+        }
+
+        let mut json = String::new();
+        json.push_str("{\"asset\":{\"version\":\"2.0\",\"generator\":\"mimesis\"}");
+        if primitives.is_empty() {
+            json.push_str(",\"scene\":0,\"scenes\":[{}]");
+        } else {
+            json.push_str(",\"scene\":0,\"scenes\":[{\"nodes\":[0]}]");
+            json.push_str(",\"nodes\":[{\"name\":\"Mesh3D\",\"mesh\":0}]");
+
+            // Mesh
+            json.push_str(",\"meshes\":[{\"name\":\"Mesh3D\",\"primitives\":[");
+            for (i, (indices, material)) in primitives.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                let _ = write!(
+                    json,
+                    "{{\"attributes\":{{\"POSITION\":{},\"NORMAL\":{},\"TEXCOORD_0\":{}}},\"indices\":{},\"material\":{},\"mode\":{}}}",
+                    position_accessor, normal_accessor, texcoord_accessor, indices, material, GL_TRIANGLES
+                );
+            }
+            json.push_str("]}]");
+        }
+
+        // Materials, one per face group
+        if !self.faces.is_empty() {
+            json.push_str(",\"materials\":[");
+            for (i, group) in self.faces.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                let _ = write!(
+                    json,
+                    "{{\"name\":\"{}\",\"pbrMetallicRoughness\":{{\"baseColorTexture\":{{\"index\":{}}},\"metallicFactor\":0.0,\"roughnessFactor\":1.0}}}}",
+                    escape_json(group.name), texture_slot(group.name)
+                );
+            }
+            json.push(']');
+        }
+
+        // Textures
+        json.push_str(",\"samplers\":[{\"magFilter\":9729,\"minFilter\":9987,\"wrapS\":10497,\"wrapT\":10497}]");
+        json.push_str(",\"textures\":[");
+        for (i, source) in image_sources.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            let _ = write!(json, "{{\"sampler\":0,\"source\":{}}}", source);
+        }
+        json.push(']');
+        let _ = write!(json, ",\"images\":[{}]", unique_images.join(","));
+
+        // Accessors, buffer views and buffer, which cannot be empty when present
+        if !accessors.is_empty() {
+            json.push_str(",\"accessors\":[");
+            for (i, accessor) in accessors.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                let _ = write!(
+                    json,
+                    "{{\"bufferView\":{},\"componentType\":{},\"count\":{},\"type\":\"{}\",\"min\":[{}],\"max\":[{}]}}",
+                    accessor.buffer_view,
+                    accessor.component_type,
+                    accessor.count,
+                    accessor.kind,
+                    join_numbers(&accessor.min),
+                    join_numbers(&accessor.max)
+                );
+            }
+            json.push(']');
+        }
+
+        if !buffer_views.is_empty() {
+            json.push_str(",\"bufferViews\":[");
+            for (i, view) in buffer_views.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                let _ = write!(json, "{{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{}", view.offset, view.length);
+                if let Some(target) = view.target {
+                    let _ = write!(json, ",\"target\":{}", target);
+                }
+                json.push('}');
+            }
+            json.push(']');
+        }
+
+        if !buffer.is_empty() {
+            match buffer_uri {
+                Some(uri) => {
+                    let _ = write!(json, ",\"buffers\":[{{\"byteLength\":{},\"uri\":\"{}\"}}]", buffer.len(), escape_json(uri));
+                }
+                None => {
+                    let _ = write!(json, ",\"buffers\":[{{\"byteLength\":{}}}]", buffer.len());
+                }
+            }
+        }
+        json.push('}');
+
+        (json, buffer)
+    }
+
+    /// Area weighted vertex normals computed from the face groups, used when the mesh has no
+    /// normals of its own.
+    fn vertex_normals(&self) -> Vec<[f64; 3]> {
+        let mut normals = vec![[0.0; 3]; self.vertices.len()];
+
+        for [i0, i1, i2] in self.faces.iter().flat_map(|group| group.indices.iter()) {
+            let normal = face_normal(self.vertices[*i0], self.vertices[*i1], self.vertices[*i2]);
+            for &i in &[*i0, *i1, *i2] {
+                normals[i][0] += normal[0];
+                normals[i][1] += normal[1];
+                normals[i][2] += normal[2];
+            }
+        }
+
+        for normal in &mut normals {
+            let length = (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt();
+            if length > 0.0 {
+                normal[0] /= length;
+                normal[1] /= length;
+                normal[2] /= length;
+            } else {
+                *normal = [0.0, 0.0, 1.0];
+            }
+        }
+
+        normals
+    }
+}
+
+/// Append a float vertex attribute to the buffer and register its buffer view and accessor.
+fn push_vertex_accessor<const N: usize>(
+    buffer: &mut Vec<u8>,
+    buffer_views: &mut Vec<BufferView>,
+    accessors: &mut Vec<Accessor>,
+    values: &[[f64; N]],
+    kind: &'static str,
+) -> usize {
+    let offset = buffer.len();
+    let mut min = [f64::INFINITY; N];
+    let mut max = [f64::NEG_INFINITY; N];
+
+    for value in values {
+        for c in 0..N {
+            let component = value[c] as f32;
+            min[c] = min[c].min(component as f64);
+            max[c] = max[c].max(component as f64);
+            buffer.extend_from_slice(&component.to_le_bytes());
+        }
+    }
+
+    if values.is_empty() {
+        min = [0.0; N];
+        max = [0.0; N];
+    }
+
+    buffer_views.push(BufferView { offset, length: buffer.len() - offset, target: Some(GL_ARRAY_BUFFER) });
+    accessors.push(Accessor {
+        buffer_view: buffer_views.len() - 1,
+        component_type: GL_FLOAT,
+        count: values.len(),
+        kind,
+        min: min.to_vec(),
+        max: max.to_vec(),
+    });
+    accessors.len() - 1
+}
+
+/// Index of the texture used by a face group in the `[front, back, side]` images.
+fn texture_slot(group_name: &str) -> usize {
+    match group_name {
+        "front" => 0,
+        "back" => 1,
+        _ => 2,
+    }
+}
+
+/// Read a PNG or JPEG texture to embed it in a GLB file.
+fn embedded_image(path: &Path) -> anyhow::Result<GltfImage> {
+    let bytes = fs::read(path)?;
+    let mime_type = image_mime_type(&bytes)
+        .ok_or_else(|| anyhow!("Unsupported glTF texture format: {}", path.display()))?;
+    Ok(GltfImage::Embedded { bytes, mime_type })
+}
+
+fn pad_to_alignment(buffer: &mut Vec<u8>) {
+    buffer.resize(buffer.len().next_multiple_of(4), 0);
+}
+
+fn same_image(a: &GltfImage, b: &GltfImage) -> bool {
+    match (a, b) {
+        (GltfImage::Uri(a), GltfImage::Uri(b)) => a == b,
+        (GltfImage::Embedded { bytes: a, .. }, GltfImage::Embedded { bytes: b, .. }) => a == b,
+        _ => false,
+    }
+}
+
+/// Detect the MIME type of a PNG or JPEG image from its signature.
+fn image_mime_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n']) {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else {
+        None
+    }
+}
+
+fn join_numbers(values: &[f64]) -> String {
+    values.iter().map(|v| format!("{}", v)).collect::<Vec<_>>().join(",")
+}
+
+fn escape_json(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
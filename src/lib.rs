@@ -1,11 +1,36 @@
+mod bezier;
 mod binary_image;
+mod bit_rows;
+mod components;
 mod contour;
+mod distance;
+mod gltf;
+mod marching_squares;
+mod mask;
+mod morphology;
 mod pixel;
+mod simplify;
+mod smooth;
+mod ply;
+mod stl;
+mod svg;
 pub mod mesh;
 pub mod draw;
 #[cfg(feature = "background-remover")]
 mod background_remover;
+#[cfg(feature = "parallel")]
+mod parallel;
 
+pub use crate::bezier::{fit_bezier, BezierPolygon, CubicBezier};
 pub use crate::binary_image::BinaryImage;
+pub use crate::components::{ComponentStats, Components, Connectivity};
+pub use crate::contour::{ContourNode, ContourTree, TraceOptions};
+pub use crate::distance::SignedDistanceField;
+pub use crate::marching_squares::trace_iso_polygons;
+pub use crate::mask::{border_color, histogram, AdaptiveMethod, ColorSpace, MaskChannel, ThresholdMethod};
+pub use crate::morphology::StructuringElement;
+pub use crate::simplify::{simplify_polygon, simplify_polygons, Simplified, SimplifyTarget};
+pub use crate::smooth::{smooth_polygon, SmoothCurve, SmoothOptions};
+pub use crate::svg::{export_svg, SvgPath};
 #[cfg(feature = "background-remover")]
 pub use crate::background_remover::BackgroundRemover;
\ No newline at end of file
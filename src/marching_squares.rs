@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use geo::{BoundingRect, Coord, LineString, Polygon};
+use image::GrayImage;
+use crate::components::Connectivity;
+use crate::contour::TraceOptions;
+
+/// Corners of a cell in clockwise order, as offsets from its top left sample
+const CORNERS: [(usize, usize); 4] = [(0, 0), (1, 0), (1, 1), (0, 1)];
+
+/// Trace the polygons of the regions of a grayscale image above `level` with marching squares.
+///
+/// Samples lie at pixel centers and vertices are interpolated linearly between them, so the
+/// contours follow the anti-aliased edges of an alpha or luminance channel at sub-pixel
+/// precision instead of the pixel corners. Pixels outside of the image are below the level,
+/// and contours crossing the image border run along it. Saddle cells are resolved with the
+/// mean of their four samples: the regions above the level are joined when it is above the
+/// level, and when it is equal to it with an 8-connected foreground in `options`, so that
+/// on a binary image the polygons are the connected components of the foreground.
+///
+/// Polygons use the coordinates of the corner tracer, where pixel `(x, y)` covers
+/// `[x, x + 1] × [y, y + 1]`. Exteriors have a positive signed area and holes a negative one.
+/// The area filters of `options` are measured on the rings rather than in pixels, and the
+/// rings enclosed by a ring filtered out are dropped along with it, like
+/// [`BinaryImage::trace_polygons_with`](crate::BinaryImage::trace_polygons_with) does.
+pub fn trace_iso_polygons(image: &GrayImage, level: f64, options: &TraceOptions) -> Vec<Polygon> {
+    let contours = IsoContours { image, level, connectivity: options.connectivity };
+
+    // Rings come before the rings they enclose, so their parent is already sorted out
+    let mut kept: Vec<bool> = Vec::new();
+    let mut polygon_of: Vec<Option<usize>> = Vec::new();
+    let mut polygons: Vec<Polygon> = Vec::new();
+    for IsoRing { ring, parent } in contours.rings() {
+        let area = signed_area(&ring);
+        let (keep, polygon) = if area > 0.0 {
+            let parent_kept = parent.is_none_or(|parent| kept[parent]);
+            if parent_kept && passes(&ring, area, options.min_dimension, options.min_area) {
+                polygons.push(Polygon::new(ring, vec![]));
+                (true, Some(polygons.len() - 1))
+            } else {
+                (false, None)
+            }
+        } else {
+            // The parent of a hole is the exterior surrounding it
+            match parent.and_then(|parent| polygon_of[parent]) {
+                Some(owner) if area < 0.0 && passes(&ring, -area, options.min_hole_dimension, options.min_hole_area) => {
+                    polygons[owner].interiors_push(ring);
+                    (true, None)
+                }
+                _ => (false, None),
+            }
+        };
+        kept.push(keep);
+        polygon_of.push(polygon);
+    }
+
+    polygons
+}
+
+/// Shoelace area of a closed ring.
+fn signed_area(ring: &LineString) -> f64 {
+    ring.lines()
+        .map(|line| line.start.x * line.end.y - line.end.x * line.start.y)
+        .sum::<f64>() / 2.0
+}
+
+/// Whether a ring passes the dimension and area filters.
+fn passes(ring: &LineString, area: f64, min_dimension: usize, min_area: usize) -> bool {
+    let large_enough = min_dimension == 0 || ring.bounding_rect()
+        .map(|rect| rect.width() >= min_dimension as f64 && rect.height() >= min_dimension as f64)
+        .unwrap_or(false);
+    large_enough && area >= min_area as f64
+}
+
+/// Ring of the contour, see `IsoContours::rings`
+struct IsoRing {
+    ring: LineString,
+    /// Index of the innermost ring enclosing this one
+    parent: Option<usize>,
+}
+
+/// Sample grid of the marching squares, padded by one sample on each side.
+///
+/// Sample `(u, v)` is the pixel `(u - 1, v - 1)`, at the coordinates `(u - 0.5, v - 0.5)`.
+/// Edges are numbered `2 * (v * stride + u)` for the horizontal edge from sample `(u, v)` to
+/// `(u + 1, v)`, and one more for the vertical edge from `(u, v)` to `(u, v + 1)`.
+struct IsoContours<'a> {
+    image: &'a GrayImage,
+    level: f64,
+    connectivity: Connectivity,
+}
+
+impl IsoContours<'_> {
+    fn stride(&self) -> usize {
+        self.image.width() as usize + 2
+    }
+
+    /// Value of a sample, `None` in the padding.
+    fn value(&self, u: usize, v: usize) -> Option<f64> {
+        let (width, height) = self.image.dimensions();
+        if u == 0 || v == 0 || u > width as usize || v > height as usize {
+            return None;
+        }
+        Some(self.image.get_pixel(u as u32 - 1, v as u32 - 1).0[0] as f64)
+    }
+
+    fn inside(&self, u: usize, v: usize) -> bool {
+        self.value(u, v).is_some_and(|value| value > self.level)
+    }
+
+    /// Point where the contour crosses an edge.
+    fn crossing(&self, edge: usize) -> Coord {
+        let sample = edge / 2;
+        let (u, v) = (sample % self.stride(), sample / self.stride());
+        let (u1, v1) = if edge.is_multiple_of(2) { (u + 1, v) } else { (u, v + 1) };
+
+        // Edges reaching the padding are crossed on the image border
+        let t = match (self.value(u, v), self.value(u1, v1)) {
+            (Some(a), Some(b)) => ((self.level - a) / (b - a)).clamp(0.0, 1.0),
+            _ => 0.5,
+        };
+        Coord {
+            x: u as f64 - 0.5 + t * (u1 as f64 - u as f64),
+            y: v as f64 - 0.5 + t * (v1 as f64 - v as f64),
+        }
+    }
+
+    /// Closed rings of the contour along with their nesting, enclosing rings first.
+    ///
+    /// Every edge crossed by the contour belongs to a single ring. The rows of samples are
+    /// then scanned like the cracks of the corner tracer: the crossings met along a row open
+    /// and close the rings enclosing the current sample, and the parent of a ring is the
+    /// innermost ring open when it is met for the first time.
+    fn rings(&self) -> Vec<IsoRing> {
+        let (width, height) = (self.image.width() as usize, self.image.height() as usize);
+        let stride = self.stride();
+
+        // Segments of every cell, from the edge where the foreground is left to the edge
+        // where it is entered when turning clockwise around the cell, so that the foreground
+        // always lies on the same side of the rings
+        let mut starts = Vec::new();
+        let mut next: HashMap<usize, usize> = HashMap::new();
+        for v in 0..=height {
+            for u in 0..=width {
+                let inside = CORNERS.map(|(du, dv)| self.inside(u + du, v + dv));
+                let edges = [
+                    2 * (v * stride + u),
+                    2 * (v * stride + u + 1) + 1,
+                    2 * ((v + 1) * stride + u),
+                    2 * (v * stride + u) + 1,
+                ];
+
+                // Edges between the corners `k` and `k + 1`, flagged when leaving the foreground
+                let transitions: Vec<(usize, bool)> = (0..4)
+                    .filter(|&k| inside[k] != inside[(k + 1) % 4])
+                    .map(|k| (edges[k], inside[k]))
+                    .collect();
+                let joined = transitions.len() == 4 && self.saddle_joined(u, v);
+
+                let n = transitions.len();
+                for (k, &(edge, leaving)) in transitions.iter().enumerate() {
+                    if leaving {
+                        let (target, _) = if joined {
+                            transitions[(k + 1) % n]
+                        } else {
+                            transitions[(k + n - 1) % n]
+                        };
+                        starts.push(edge);
+                        next.insert(edge, target);
+                    }
+                }
+            }
+        }
+
+        let mut rings: Vec<Option<LineString>> = Vec::new();
+        let mut ring_of: HashMap<usize, usize> = HashMap::new();
+        for start in starts {
+            if !next.contains_key(&start) {
+                continue;
+            }
+
+            let mut ring = Vec::new();
+            let mut edge = start;
+            while let Some(target) = next.remove(&edge) {
+                ring_of.insert(edge, rings.len());
+                ring.push(self.crossing(edge));
+                edge = target;
+            }
+            let ring = remove_collinear(ring);
+            rings.push((ring.len() >= 3).then(|| {
+                let mut ring = LineString::from(ring);
+                ring.close();
+                ring
+            }));
+        }
+
+        // Rings in the order they are met, along with the index of their parent in that order
+        let mut order = vec![usize::MAX; rings.len()];
+        let mut nested: Vec<(usize, Option<usize>)> = Vec::with_capacity(rings.len());
+        let mut open: Vec<usize> = Vec::new();
+        for v in 1..=height {
+            open.clear();
+            for u in 0..=width {
+                let Some(&ring) = ring_of.get(&(2 * (v * stride + u))) else {
+                    continue;
+                };
+                if order[ring] == usize::MAX {
+                    order[ring] = nested.len();
+                    nested.push((ring, open.last().map(|&parent| order[parent])));
+                    open.push(ring);
+                } else if open.last() == Some(&ring) {
+                    open.pop();
+                } else {
+                    open.push(ring);
+                }
+            }
+        }
+
+        // Degenerate rings are left out, the rings they would enclose are too small to exist
+        let mut index = vec![None; nested.len()];
+        let mut result = Vec::with_capacity(nested.len());
+        for (i, (ring, parent)) in nested.into_iter().enumerate() {
+            if let Some(ring) = rings[ring].take() {
+                index[i] = Some(result.len());
+                result.push(IsoRing { ring, parent: parent.and_then(|parent| index[parent]) });
+            }
+        }
+        result
+    }
+
+    /// Whether the foreground corners of a saddle cell are connected through its center.
+    fn saddle_joined(&self, u: usize, v: usize) -> bool {
+        let sum: f64 = CORNERS.iter()
+            .filter_map(|&(du, dv)| self.value(u + du, v + dv))
+            .sum();
+        let mean = sum / 4.0;
+        mean > self.level || (mean == self.level && self.connectivity == Connectivity::Eight)
+    }
+}
+
+/// Remove the vertices of an open ring lying on the line between their neighbours.
+fn remove_collinear(ring: Vec<Coord>) -> Vec<Coord> {
+    let collinear = |a: Coord, b: Coord, c: Coord| {
+        ((b.x - a.x) * (c.y - b.y) - (b.y - a.y) * (c.x - b.x)).abs() < 1e-9
+    };
+
+    let n = ring.len();
+    let mut kept: Vec<Coord> = Vec::with_capacity(n);
+    for k in 0..n {
+        let previous = kept.last().copied().unwrap_or(ring[(k + n - 1) % n]);
+        if !collinear(previous, ring[k], ring[(k + 1) % n]) {
+            kept.push(ring[k]);
+        }
+    }
+
+    // The first vertices were compared to the last ones before those were simplified
+    let mut start = 0;
+    while kept.len() - start >= 3 {
+        let last = kept[kept.len() - 1];
+        if collinear(last, kept[start], kept[start + 1]) {
+            start += 1;
+        } else if collinear(kept[kept.len() - 2], last, kept[start]) {
+            kept.pop();
+        } else {
+            break;
+        }
+    }
+    kept.split_off(start)
+}
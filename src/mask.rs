@@ -0,0 +1,352 @@
+use image::{DynamicImage, GrayImage};
+use bit_vec::BitVec;
+use crate::binary_image::BinaryImage;
+
+/// Channel of an image a mask is built from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MaskChannel {
+    /// Brightness of the pixels
+    Luminance,
+    /// Opacity of the pixels
+    #[default]
+    Alpha,
+    Red,
+    Green,
+    Blue,
+}
+
+impl MaskChannel {
+    /// Extract the channel as a grayscale image.
+    pub fn extract(&self, image: &DynamicImage) -> GrayImage {
+        let channel = match self {
+            MaskChannel::Luminance => return image.to_luma8(),
+            MaskChannel::Alpha => 3,
+            MaskChannel::Red => 0,
+            MaskChannel::Green => 1,
+            MaskChannel::Blue => 2,
+        };
+
+        let rgba = image.to_rgba8();
+        GrayImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+            image::Luma([rgba.get_pixel(x, y).0[channel]])
+        })
+    }
+}
+
+/// How the threshold separating the foreground from the background is chosen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThresholdMethod {
+    /// Given threshold
+    Fixed(u8),
+    /// Otsu's method, maximizing the variance between the foreground and the background.
+    /// Suited to images with two distinct peaks in their histogram.
+    Otsu,
+    /// Triangle method, finding the knee of the histogram between its peak and its longest
+    /// tail. Suited to a small foreground on a uniform background, or the reverse.
+    Triangle,
+    /// Threshold below which the given percentage of the pixels lie, from 0 to 100
+    Percentile(f64),
+}
+
+impl ThresholdMethod {
+    /// Threshold chosen for the histogram of an 8-bit channel.
+    pub fn threshold(&self, histogram: &[u64; 256]) -> u8 {
+        match *self {
+            ThresholdMethod::Fixed(threshold) => threshold,
+            ThresholdMethod::Otsu => otsu(histogram),
+            ThresholdMethod::Triangle => triangle(histogram),
+            ThresholdMethod::Percentile(percentile) => percentile_threshold(histogram, percentile),
+        }
+    }
+}
+
+/// Local threshold of [`BinaryImage::from_channel_adaptive`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AdaptiveMethod {
+    /// Mean of the window around the pixel
+    #[default]
+    Mean,
+    /// Gaussian weighted mean of the window around the pixel, less sensitive to details on
+    /// the edge of the window. Approximated by three passes of a box filter.
+    Gaussian,
+}
+
+/// Number of pixels of every value of a grayscale image.
+pub fn histogram(image: &GrayImage) -> [u64; 256] {
+    let mut histogram = [0u64; 256];
+    for pixel in image.pixels() {
+        histogram[pixel.0[0] as usize] += 1;
+    }
+    histogram
+}
+
+/// Color space in which the distance between two colors is measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// CIE L*a*b*, the distance is the CIE76 color difference ΔE, where 2.3 is about a just
+    /// noticeable difference
+    #[default]
+    Lab,
+    /// HSV cone, scaled so that black and white are 100 apart. Less perceptual than Lab but
+    /// more tolerant of the lighting variations of a screen, whose hue stays the same.
+    Hsv,
+}
+
+impl ColorSpace {
+    /// Distance between two sRGB colors.
+    pub fn distance(&self, a: [u8; 3], b: [u8; 3]) -> f64 {
+        euclidean(self.coordinates(a), self.coordinates(b))
+    }
+
+    /// Cartesian coordinates of an sRGB color in the color space.
+    fn coordinates(&self, rgb: [u8; 3]) -> [f64; 3] {
+        match self {
+            ColorSpace::Lab => lab(rgb),
+            ColorSpace::Hsv => {
+                let max = *rgb.iter().max().unwrap_or(&0) as f64;
+                let min = *rgb.iter().min().unwrap_or(&0) as f64;
+                // Radius of the cone is the chroma, i.e. saturation times value
+                let chroma = (max - min) / 255.0 * 100.0;
+                let hue = hue(rgb).to_radians();
+                [chroma * hue.cos(), chroma * hue.sin(), max / 255.0 * 100.0]
+            }
+        }
+    }
+}
+
+/// Guess the background color of an image from its corners.
+///
+/// Takes the median of every channel over four squares in the corners of the image, each
+/// 1/32 of the smallest dimension wide, so that the subject may cover one of them.
+pub fn border_color(image: &DynamicImage) -> [u8; 3] {
+    let rgb = image.to_rgb8();
+    let (width, height) = rgb.dimensions();
+    if width == 0 || height == 0 {
+        return [0; 3];
+    }
+
+    let size = (width.min(height) / 32).max(1);
+    let mut channels: [Vec<u8>; 3] = Default::default();
+    for (left, top) in [(0, 0), (width - size, 0), (0, height - size), (width - size, height - size)] {
+        for y in top..top + size {
+            for x in left..left + size {
+                let pixel = rgb.get_pixel(x, y).0;
+                for (channel, &value) in channels.iter_mut().zip(pixel.iter()) {
+                    channel.push(value);
+                }
+            }
+        }
+    }
+
+    channels.map(|mut channel| {
+        channel.sort_unstable();
+        channel[channel.len() / 2]
+    })
+}
+
+impl BinaryImage {
+    /// Build a mask from a channel of an image with a threshold local to every pixel: pixels
+    /// above the mean of the `window` × `window` square around them minus `offset` are
+    /// foreground.
+    ///
+    /// Follows gradients of the lighting that a global threshold cannot, as long as the
+    /// window is larger than the details to separate: uniform regions are foreground when
+    /// the offset is positive. Means are computed over summed-area tables, so the cost does
+    /// not depend on the window size.
+    pub fn from_channel_adaptive(image: &DynamicImage, channel: MaskChannel, method: AdaptiveMethod, window: u32, offset: f64) -> BinaryImage {
+        let gray = channel.extract(image);
+        let (width, height) = (gray.width() as usize, gray.height() as usize);
+        let values: Vec<f64> = gray.iter().map(|&value| value as f64).collect();
+
+        let radius = (window / 2) as usize;
+        let means = match method {
+            AdaptiveMethod::Mean => box_mean(&values, width, height, radius),
+            AdaptiveMethod::Gaussian => {
+                // Three passes of a box spanning a third of the window, small windows would
+                // otherwise leave a box of a single pixel
+                let radius = (radius / 3).max(1);
+                let means = box_mean(&values, width, height, radius);
+                let means = box_mean(&means, width, height, radius);
+                box_mean(&means, width, height, radius)
+            }
+        };
+
+        let buffer = values.iter().zip(means)
+            .map(|(&value, mean)| value > mean - offset)
+            .collect();
+        BinaryImage::from_bitvec(gray.width(), gray.height(), buffer)
+    }
+
+    /// Build a mask keying out a color: pixels further than `tolerance` from `key` in the
+    /// given color space are foreground. Fully transparent pixels are always background.
+    pub fn from_color_key(image: &DynamicImage, key: [u8; 3], tolerance: f64, space: ColorSpace) -> BinaryImage {
+        let rgba = image.to_rgba8();
+        let key = space.coordinates(key);
+
+        // Images hold few distinct colors compared to their pixels, the last one is cached
+        let mut last: Option<([u8; 3], bool)> = None;
+        let buffer: BitVec = rgba.pixels().map(|pixel| {
+            let [r, g, b, a] = pixel.0;
+            if a == 0 {
+                return false;
+            }
+            match last {
+                Some((color, foreground)) if color == [r, g, b] => foreground,
+                _ => {
+                    let foreground = euclidean(space.coordinates([r, g, b]), key) > tolerance;
+                    last = Some(([r, g, b], foreground));
+                    foreground
+                }
+            }
+        }).collect();
+
+        BinaryImage::from_bitvec(rgba.width(), rgba.height(), buffer)
+    }
+
+    /// Build a mask from a channel of an image: pixels strictly above the threshold are
+    /// foreground.
+    ///
+    /// Returns the mask along with the threshold used, which is only known after the fact
+    /// for automatic methods.
+    pub fn from_channel(image: &DynamicImage, channel: MaskChannel, method: ThresholdMethod) -> (BinaryImage, u8) {
+        let gray = channel.extract(image);
+        let threshold = method.threshold(&histogram(&gray));
+        let buffer = gray.pixels().map(|pixel| pixel.0[0] > threshold).collect();
+        (BinaryImage::from_bitvec(gray.width(), gray.height(), buffer), threshold)
+    }
+}
+
+/// Mean of the `(2 * radius + 1)` square around every value of a row major grid, the square
+/// being cropped to the grid.
+fn box_mean(values: &[f64], width: usize, height: usize, radius: usize) -> Vec<f64> {
+    // Summed-area table with a leading row and column of zeros
+    let stride = width + 1;
+    let mut sums = vec![0f64; stride * (height + 1)];
+    for y in 0..height {
+        let mut row_sum = 0.0;
+        for x in 0..width {
+            row_sum += values[y * width + x];
+            sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row_sum;
+        }
+    }
+
+    let mut means = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let (top, bottom) = (y.saturating_sub(radius), (y + radius + 1).min(height));
+        for x in 0..width {
+            let (left, right) = (x.saturating_sub(radius), (x + radius + 1).min(width));
+            let sum = sums[bottom * stride + right] - sums[top * stride + right] -
+                sums[bottom * stride + left] + sums[top * stride + left];
+            means.push(sum / ((bottom - top) * (right - left)) as f64);
+        }
+    }
+    means
+}
+
+fn otsu(histogram: &[u64; 256]) -> u8 {
+    let total: u64 = histogram.iter().sum();
+    let sum: f64 = histogram.iter().enumerate().map(|(value, &count)| value as f64 * count as f64).sum();
+
+    let mut best = (0, f64::MIN);
+    let mut background_count = 0u64;
+    let mut background_sum = 0f64;
+    for (value, &count) in histogram.iter().enumerate() {
+        background_count += count;
+        background_sum += value as f64 * count as f64;
+        let foreground_count = total - background_count;
+        if background_count == 0 || foreground_count == 0 {
+            continue;
+        }
+
+        let background_mean = background_sum / background_count as f64;
+        let foreground_mean = (sum - background_sum) / foreground_count as f64;
+        let variance = background_count as f64 * foreground_count as f64 * (background_mean - foreground_mean).powi(2);
+        if variance > best.1 {
+            best = (value, variance);
+        }
+    }
+    best.0 as u8
+}
+
+fn triangle(histogram: &[u64; 256]) -> u8 {
+    let (Some(min), Some(max)) = (
+        histogram.iter().position(|&count| count > 0),
+        histogram.iter().rposition(|&count| count > 0),
+    ) else {
+        return 0;
+    };
+    let peak = (min..=max).max_by_key(|&value| histogram[value]).unwrap_or(min);
+
+    // Line from the peak to the end of the longest tail, the threshold is the value of the
+    // histogram furthest below it. The peak stays above the threshold when the tail is on
+    // the dark side, so that the mask is never empty.
+    let end = if peak - min > max - peak { min } else { max };
+    if end == peak {
+        return peak.min(254) as u8;
+    }
+    let (x0, y0) = (peak as f64, histogram[peak] as f64);
+    let (x1, y1) = (end as f64, histogram[end] as f64);
+
+    let sign = if end < peak { -1.0 } else { 1.0 };
+    let distance = |value: usize| sign * ((y1 - y0) * value as f64 - (x1 - x0) * histogram[value] as f64);
+    let range = if end < peak { end..=peak - 1 } else { peak..=end };
+    range
+        .max_by(|&a, &b| distance(a).total_cmp(&distance(b)))
+        .unwrap_or(peak) as u8
+}
+
+fn percentile_threshold(histogram: &[u64; 256], percentile: f64) -> u8 {
+    let total: u64 = histogram.iter().sum();
+    let target = (total as f64 * percentile.clamp(0.0, 100.0) / 100.0).ceil() as u64;
+
+    let mut count = 0;
+    for (value, &value_count) in histogram.iter().enumerate() {
+        count += value_count;
+        if count >= target {
+            return value as u8;
+        }
+    }
+    u8::MAX
+}
+
+fn euclidean(a: [f64; 3], b: [f64; 3]) -> f64 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+/// Hue of an sRGB color in degrees.
+fn hue(rgb: [u8; 3]) -> f64 {
+    let [r, g, b] = rgb.map(|value| value as f64);
+    let max = r.max(g).max(b);
+    let chroma = max - r.min(g).min(b);
+    if chroma == 0.0 {
+        return 0.0;
+    }
+
+    let sector = if max == r {
+        ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+    sector * 60.0
+}
+
+/// CIE L*a*b* coordinates of an sRGB color, under the D65 illuminant.
+fn lab(rgb: [u8; 3]) -> [f64; 3] {
+    let [r, g, b] = rgb.map(|value| {
+        let value = value as f64 / 255.0;
+        if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+    });
+
+    let x = (0.4124564 * r + 0.3575761 * g + 0.1804375 * b) / 0.95047;
+    let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+    let z = (0.0193339 * r + 0.1191920 * g + 0.9503041 * b) / 1.08883;
+
+    let f = |t: f64| {
+        const DELTA: f64 = 6.0 / 29.0;
+        if t > DELTA.powi(3) { t.cbrt() } else { t / (3.0 * DELTA * DELTA) + 4.0 / 29.0 }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
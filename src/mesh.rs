@@ -1,8 +1,17 @@
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufWriter, Write};
+use std::ops::Range;
 use std::path::Path;
+use anyhow::anyhow;
 use earcutr::earcut;
-use geo::Polygon;
+use geo::{BoundingRect, Contains, Distance, Euclidean, LineString, Point, Polygon, Validation};
+use image::{GrayImage, ImageBuffer, Luma, Primitive};
+use spade::handles::FixedVertexHandle;
+use spade::{AngleLimit, ConstrainedDelaunayTriangulation, Point2, RefinementParameters, Triangulation};
+
+pub use crate::ply::PlyFormat;
+pub use crate::stl::StlFormat;
 
 #[derive(Debug, Clone)]
 pub struct MeshGroup {
@@ -14,6 +23,8 @@ pub struct MeshGroup {
 pub struct Mesh3D {
     pub vertices: Vec<[f64; 3]>,
     pub uvs: Vec<[f64; 2]>,
+    /// Per-vertex unit normals, empty until [`Mesh3D::compute_normals`] is called
+    pub normals: Vec<[f64; 3]>,
     pub faces: Vec<MeshGroup>,
 }
 
@@ -39,18 +50,36 @@ impl Mesh3D {
             writeln!(writer, "vt {} {}", u, v)?;
         }
 
+        // Write normals
+        let has_normals = self.has_normals();
+        if has_normals {
+            for [x, y, z] in &self.normals {
+                writeln!(writer, "vn {} {} {}", x, y, z)?;
+            }
+        }
+
         // Write face groups
         for group in &self.faces {
             writeln!(writer, "usemtl {}", group.name)?;
             writeln!(writer, "g {}", group.name)?;
             for [i0, i1, i2] in &group.indices {
-                writeln!(
-                    writer,
-                    "f {0}/{0} {1}/{1} {2}/{2}",
-                    i0 + 1,
-                    i1 + 1,
-                    i2 + 1
-                )?;
+                if has_normals {
+                    writeln!(
+                        writer,
+                        "f {0}/{0}/{0} {1}/{1}/{1} {2}/{2}/{2}",
+                        i0 + 1,
+                        i1 + 1,
+                        i2 + 1
+                    )?;
+                } else {
+                    writeln!(
+                        writer,
+                        "f {0}/{0} {1}/{1} {2}/{2}",
+                        i0 + 1,
+                        i1 + 1,
+                        i2 + 1
+                    )?;
+                }
             }
         }
 
@@ -102,6 +131,93 @@ impl Mesh3D {
     pub fn get_faces(&self) -> &Vec<MeshGroup> {
         &self.faces
     }
+
+    #[inline]
+    pub fn has_normals(&self) -> bool {
+        !self.normals.is_empty() && self.normals.len() == self.vertices.len()
+    }
+
+    /// Compute per-vertex normals, splitting vertices along hard edges.
+    ///
+    /// Within a face group, the normals of adjacent triangles are averaged, weighted by their
+    /// angle at the vertex, when the angle between them is below `crease_angle` (in radians),
+    /// otherwise the edge stays hard. Triangles of different groups are never averaged, so the
+    /// front, back and side boundaries always stay hard. Vertices whose triangles end up with
+    /// different normals are duplicated.
+    pub fn compute_normals(&mut self, crease_angle: f64) {
+        let cos_crease = crease_angle.cos();
+
+        // Unit face normals
+        let face_normals: Vec<Vec<[f64; 3]>> = self.faces.iter()
+            .map(|group| group.indices.iter()
+                .map(|[i0, i1, i2]| normalize(face_normal(self.vertices[*i0], self.vertices[*i1], self.vertices[*i2])))
+                .collect())
+            .collect();
+
+        let mut normals: Vec<Option<[f64; 3]>> = vec![None; self.vertices.len()];
+        let mut duplicates: HashMap<(usize, [u64; 3]), usize> = HashMap::new();
+
+        for (group_index, group) in self.faces.iter_mut().enumerate() {
+            // Triangle corners incident to each position, so that seams in the UV mapping do
+            // not break the smoothing
+            let mut incident: HashMap<[u64; 3], Vec<(usize, f64)>> = HashMap::new();
+            for (face_index, triangle) in group.indices.iter().enumerate() {
+                for corner in 0..3 {
+                    let p = self.vertices[triangle[corner]];
+                    let a = self.vertices[triangle[(corner + 1) % 3]];
+                    let b = self.vertices[triangle[(corner + 2) % 3]];
+                    incident.entry(position_key(p)).or_default().push((face_index, corner_angle(p, a, b)));
+                }
+            }
+
+            for face_index in 0..group.indices.len() {
+                let own_normal = face_normals[group_index][face_index];
+
+                for corner in 0..3 {
+                    let vertex = group.indices[face_index][corner];
+
+                    let mut normal = [0.0; 3];
+                    for &(other, angle) in &incident[&position_key(self.vertices[vertex])] {
+                        let other_normal = face_normals[group_index][other];
+                        let cos = own_normal[0] * other_normal[0] + own_normal[1] * other_normal[1] + own_normal[2] * other_normal[2];
+                        if other == face_index || cos >= cos_crease {
+                            normal[0] += other_normal[0] * angle;
+                            normal[1] += other_normal[1] * angle;
+                            normal[2] += other_normal[2] * angle;
+                        }
+                    }
+                    let normal = if normal == [0.0; 3] { own_normal } else { normalize(normal) };
+
+                    // Reuse the vertex if it has no normal yet or the same one, otherwise
+                    // split it
+                    match normals[vertex] {
+                        None => normals[vertex] = Some(normal),
+                        Some(existing) if existing == normal => {}
+                        Some(_) => {
+                            let key = (vertex, normal.map(f64::to_bits));
+                            let duplicate = *duplicates.entry(key).or_insert_with(|| {
+                                self.vertices.push(self.vertices[vertex]);
+                                self.uvs.push(self.uvs[vertex]);
+                                normals.push(Some(normal));
+                                self.vertices.len() - 1
+                            });
+                            group.indices[face_index][corner] = duplicate;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.normals = normals.into_iter()
+            .map(|normal| normal.unwrap_or([0.0, 0.0, 1.0]))
+            .collect();
+    }
+}
+
+impl Default for Mesh3D {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Mesh3D {
@@ -109,15 +225,89 @@ impl Mesh3D {
         Self {
             vertices: Vec::new(),
             uvs: Vec::new(),
+            normals: Vec::new(),
             faces: Vec::new(),
         }
     }
 }
 
+/// Shape of the edges of an extruded mesh
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExtrusionProfile {
+    /// Straight walls at 90° to the front and back faces
+    Flat,
+    /// Straight cut of the given width on the front and back edges
+    Chamfer { width: f64 },
+    /// Quarter circle of the given width on the front and back edges, with `segments` quads
+    Round { width: f64, segments: usize },
+}
+
+impl ExtrusionProfile {
+    /// `(inset, z)` pairs describing the side walls from the back face to the front face.
+    ///
+    /// The bevel width is clamped to half the depth.
+    fn samples(&self, depth: f64) -> Vec<(f64, f64)> {
+        let (width, segments) = match *self {
+            ExtrusionProfile::Flat => (0.0, 1),
+            ExtrusionProfile::Chamfer { width } => (width, 1),
+            ExtrusionProfile::Round { width, segments } => (width, segments.max(1)),
+        };
+        let width = width.clamp(0.0, depth / 2.0);
+        if width <= 0.0 {
+            return vec![(0.0, 0.0), (0.0, depth)];
+        }
+
+        // Back bevel, from the inset back face to the outer contour
+        let mut samples: Vec<(f64, f64)> = (0..=segments)
+            .map(|k| {
+                let angle = k as f64 / segments as f64 * std::f64::consts::FRAC_PI_2;
+                match self {
+                    ExtrusionProfile::Round { .. } => (width * (1.0 - angle.sin()), width * (1.0 - angle.cos())),
+                    _ => (width * (1.0 - k as f64 / segments as f64), width * k as f64 / segments as f64),
+                }
+            })
+            .collect();
+
+        // Front bevel, mirrored
+        let front: Vec<(f64, f64)> = samples.iter().rev().map(|&(inset, z)| (inset, depth - z)).collect();
+        if samples.last() == front.first() {
+            samples.pop();
+        }
+        samples.extend(front);
+        samples
+    }
+}
+
+/// Cross-section of an inflated mesh, see [`Mesh2D::inflate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InflateProfile {
+    /// Quarter circle, steep near the contour and flat in the middle
+    Circular,
+    /// Parabola, softer than the circle near the contour
+    Parabolic,
+    /// Straight slope from the contour to the thickest point
+    Linear,
+}
+
+impl InflateProfile {
+    /// Height of the profile at the relative distance `t` in `[0, 1]` between the contour and
+    /// the thickest point.
+    fn height(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            InflateProfile::Circular => (1.0 - (1.0 - t) * (1.0 - t)).sqrt(),
+            InflateProfile::Parabolic => 1.0 - (1.0 - t) * (1.0 - t),
+            InflateProfile::Linear => t,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Mesh2D {
     pub vertices: Vec<[f64; 2]>,
     pub indices: Vec<usize>,
+    /// Ranges of `vertices` forming each ring of the polygon, exterior ring first
+    pub rings: Vec<Range<usize>>,
 }
 
 impl Mesh2D {
@@ -145,111 +335,239 @@ impl Mesh2D {
     }
 
     pub fn extrude(&self, depth: f64, image_width: f64, image_height: f64) -> Mesh3D {
+        let loops = self.ring_loops();
+        self.build_extrusion(&loops, &loops, &[(0.0, 0.0), (0.0, depth)], image_width, image_height)
+    }
+
+    /// Extrude the mesh with beveled or rounded edges.
+    ///
+    /// The front and back faces are inset by the bevel width and re-triangulated, and the
+    /// side walls are built as rings of quads following the profile from the inset contours
+    /// to the outer contours. Where a feature of the polygon is narrower than twice the bevel,
+    /// the inset is reduced locally so that the bevel gets steeper instead of folding over,
+    /// see `inset_loops`. The inset faces are triangulated with `triangulator`, usually the
+    /// one this mesh was built with. Returns `None` if no valid inset polygon is found, and
+    /// fails if the inset faces cannot be triangulated.
+    pub fn extrude_profile(&self, depth: f64, image_width: f64, image_height: f64, profile: ExtrusionProfile, triangulator: &Triangulator) -> anyhow::Result<Option<Mesh3D>> {
+        let samples = profile.samples(depth);
+        let loops = self.ring_loops();
+
+        let inset = samples[0].0;
+        if inset <= 0.0 {
+            return Ok(Some(self.build_extrusion(&loops, &loops, &samples, image_width, image_height)));
+        }
+
+        // Inset polygon, back in image coordinates (y down)
+        let inset_loops = inset_loops(&loops, inset);
+
+        // A ring whose inset could not be reduced enough flips over
+        let flipped = loops.iter()
+            .zip(&inset_loops)
+            .any(|(points, inset_points)| loop_area(points) * loop_area(inset_points) <= 0.0);
+        let mut rings = inset_loops.iter()
+            .map(|points| LineString::from(points.iter().map(|[x, y]| (*x, -*y)).collect::<Vec<_>>()));
+        let exterior = rings.next().ok_or_else(|| anyhow!("Cannot bevel an empty mesh"))?;
+        let inset_polygon = Polygon::new(exterior, rings.collect());
+        if flipped || !inset_polygon.is_valid() {
+            return Ok(None);
+        }
+
+        // Steiner points on the inset contours would not be shared with the side walls
+        let cap = match triangulator {
+            Triangulator::Earcut => inset_polygon.mesh2d()?,
+            Triangulator::Delaunay(refinement) => delaunay_mesh(&inset_polygon, Vec::new(), Some(refinement), false)?,
+        };
+        Ok(Some(cap.build_extrusion(&inset_loops, &loops, &samples, image_width, image_height)))
+    }
+
+    /// Extrude the mesh and move the vertices of the front and back faces along the z axis.
+    ///
+    /// `displacement` returns, for a point of the mesh in image coordinates, how far the back
+    /// face is pushed toward -z and the front face toward +z. Contour vertices are left in
+    /// place so that the faces stay connected to the side walls, the displacement only makes
+    /// sense on meshes with interior points, see [`PolygonMesh::mesh2d_with_interior`].
+    pub fn extrude_displaced<F>(&self, depth: f64, image_width: f64, image_height: f64, displacement: F) -> Mesh3D
+    where
+        F: Fn([f64; 2]) -> (f64, f64),
+    {
+        let mut mesh = self.extrude(depth, image_width, image_height);
         let n = self.vertices.len();
+        let contour_len = self.rings.iter().map(|ring| ring.end).max().unwrap_or(0);
+
+        // Back vertices are 0..n, front vertices are n..2n, and the rings come first
+        for i in contour_len..n {
+            let (back, front) = displacement(self.vertices[i]);
+            mesh.vertices[i][2] -= back;
+            mesh.vertices[i + n][2] += front;
+        }
+
+        mesh
+    }
+
+    /// Extrude the mesh into a puffy volume whose thickness follows the distance to the contour.
+    ///
+    /// `distances` is the distance transform of the mask the polygon was traced from, see
+    /// [`BinaryImage::distance_transform`](crate::BinaryImage::distance_transform). Both faces
+    /// are pushed away from the slab of thickness `depth` by `height` times the profile of the
+    /// distance, relative to the largest distance found among the vertices of the mesh. The
+    /// distance is normalised per mesh, so a small polygon meshed on its own gets as thick as a
+    /// large one.
+    pub fn inflate(&self, depth: f64, image_width: f64, image_height: f64, distances: &ImageBuffer<Luma<f32>, Vec<f32>>, height: f64, profile: InflateProfile) -> Mesh3D {
+        let distance_at = |[x, y]: [f64; 2]| sample_distance(distances, x, y);
+        let max_distance = self.vertices.iter()
+            .map(|&vertex| distance_at(vertex))
+            .fold(0.0, f64::max);
+        if max_distance <= 0.0 {
+            return self.extrude(depth, image_width, image_height);
+        }
+
+        self.extrude_displaced(depth, image_width, image_height, |vertex| {
+            let offset = height * profile.height(distance_at(vertex) / max_distance);
+            (offset, offset)
+        })
+    }
+
+    /// Extrude the mesh and raise its front face following a grayscale depth map, for
+    /// bas-relief artwork.
+    ///
+    /// White pixels of the depth map raise the front face by `scale`, black pixels leave it at
+    /// `depth`. The depth map is stretched over the image if their sizes differ. The contour
+    /// and the UV mapping are kept, only the interior vertices move, see
+    /// [`PolygonMesh::mesh2d_with_interior`].
+    pub fn relief(&self, depth: f64, image_width: f64, image_height: f64, depth_map: &GrayImage, scale: f64) -> Mesh3D {
+        let (map_width, map_height) = depth_map.dimensions();
+        let scale_x = map_width as f64 / image_width;
+        let scale_y = map_height as f64 / image_height;
+
+        self.extrude_displaced(depth, image_width, image_height, |[x, y]| {
+            let value = sample_bilinear(depth_map, x * scale_x, y * scale_y) / u8::MAX as f64;
+            (0.0, value * scale)
+        })
+    }
+
+    /// Build the front and back faces from this mesh, and the side walls by sweeping `loops`
+    /// along the profile `samples` of `(inset, z)` pairs.
+    ///
+    /// `cap_loops` are the rings of this mesh as returned by `ring_loops`, `loops` are the
+    /// outer contours the insets are measured from. The contours in between are interpolated
+    /// point by point, so that the insets reduced by `inset_loops` are followed along the
+    /// whole profile. The U coordinate of the side walls is taken from the outer contours and
+    /// the V coordinate follows the length of the profile, so the texture is continuous
+    /// around the bevel.
+    fn build_extrusion(&self, cap_loops: &[Vec<[f64; 2]>], loops: &[Vec<[f64; 2]>], samples: &[(f64, f64)], image_width: f64, image_height: f64) -> Mesh3D {
+        let n = self.vertices.len();
+        let cap_inset = samples[0].0;
+        let back_z = samples[0].1;
+        let front_z = samples[samples.len() - 1].1;
 
         // We need separate vertices for different UV mappings
-        // Structure: [bottom_vertices, top_vertices, side_vertices...]
+        // Structure: [back_vertices, front_vertices, side_vertices...]
         let mut vertices = Vec::new();
         let mut uvs = Vec::new();
         let mut front_indices = Vec::new();
         let mut back_indices = Vec::new();
         let mut side_indices = Vec::new();
 
-        // Create bottom vertices (front face) - indices 0..n-1
+        // Create back vertices (z=0) - indices 0..n-1
         for [x, y] in &self.vertices {
-            vertices.push([*x, -*y, 0.0]);
+            vertices.push([*x, -*y, back_z]);
             uvs.push([*x / image_width, -*y / image_height]);
         }
 
-        // Create top vertices (back face) - indices n..2n-1
+        // Create front vertices (z=depth) - indices n..2n-1
         for [x, y] in &self.vertices {
-            vertices.push([*x, -*y, depth]);
+            vertices.push([*x, -*y, front_z]);
             uvs.push([*x / image_width, -*y / image_height]);
         }
 
+        // Earcut triangles share the same winding, flip them so that the front face points
+        // toward +z and the back face toward -z
+        let counter_clockwise = self.triangulation_area() >= 0.0;
+
         // Generate front and back faces
         for triangle in self.indices.chunks(3) {
-            let i0 = triangle[0];
-            let i1 = triangle[1];
-            let i2 = triangle[2];
-
-            // Front face (bottom, z=0) - keep original winding
-            front_indices.push([i2 + n, i1 + n, i0 + n]);
+            let (i0, i1, i2) = if counter_clockwise {
+                (triangle[0], triangle[1], triangle[2])
+            } else {
+                (triangle[0], triangle[2], triangle[1])
+            };
 
-            // Back face (top, z=depth) - reverse winding for correct normals
-            back_indices.push([i0, i1, i2]);
+            front_indices.push([i0 + n, i1 + n, i2 + n]);
+            back_indices.push([i2, i1, i0]);
         }
 
-        // Find boundary edges for side faces
-        let mut edge_count = std::collections::HashMap::new();
-        for triangle in self.indices.chunks(3) {
-            for e in 0..3 {
-                let i0 = triangle[e];
-                let i1 = triangle[(e + 1) % 3];
-                let edge = if i0 < i1 { (i0, i1) } else { (i1, i0) };
-                *edge_count.entry(edge).or_insert(0) += 1;
-            }
+        // V coordinate of each profile sample, along the length of the profile
+        let mut profile_lengths = Vec::with_capacity(samples.len());
+        let mut profile_length = 0.0;
+        profile_lengths.push(0.0);
+        for pair in samples.windows(2) {
+            let ((i0, z0), (i1, z1)) = (pair[0], pair[1]);
+            profile_length -= ((i1 - i0).powi(2) + (z1 - z0).powi(2)).sqrt();
+            profile_lengths.push(profile_length);
         }
+        let profile_length = -profile_length;
 
-        // Collect boundary edges and sort them to form a continuous boundary
-        let mut boundary_edges: Vec<(usize, usize)> = edge_count.iter()
-            .filter(|&(_, &count)| count == 1)
-            .map(|(&(i0, i1), _)| (i0, i1))
-            .collect();
-
-        // Sort boundary edges to form continuous loops
-        boundary_edges.sort_by_key(|&(i0, i1)| i0.min(i1));
-
-        // Calculate total perimeter for UV mapping
-        let total_perimeter: f64 = boundary_edges.iter()
-            .map(|&(i0, i1)| {
-                let p0 = self.vertices[i0];
-                let p1 = self.vertices[i1];
-                ((p1[0] - p0[0]).powi(2) + (p1[1] - p0[1]).powi(2)).sqrt()
-            })
-            .sum();
-
-        // Create side faces with proper UV mapping
-        let mut current_u = 0.0;
-
-        for &(i0, i1) in &boundary_edges {
-            let p0 = self.vertices[i0];
-            let p1 = self.vertices[i1];
-            let edge_length = ((p1[0] - p0[0]).powi(2) + (p1[1] - p0[1]).powi(2)).sqrt();
+        // Generate side faces, one continuous wall per ring
+        for (ring_index, points) in loops.iter().enumerate() {
+            if points.len() < 3 {
+                continue;
+            }
 
-            let u0 = current_u / total_perimeter;
-            let u1 = (current_u + edge_length) / total_perimeter;
-            current_u += edge_length;
+            // Each ring gets its own U parametrisation along its perimeter
+            let mut distances = Vec::with_capacity(points.len() + 1);
+            let mut perimeter = 0.0;
+            distances.push(0.0);
+            for k in 0..points.len() {
+                let p0 = points[k];
+                let p1 = points[(k + 1) % points.len()];
+                perimeter += ((p1[0] - p0[0]).powi(2) + (p1[1] - p0[1]).powi(2)).sqrt();
+                distances.push(perimeter);
+            }
 
-            // Add 4 vertices for this side quad (to have unique UVs)
+            // The first point is repeated at the end of the ring to close the U seam
             let base_idx = vertices.len();
+            let count = points.len() + 1;
+            for (s, &(inset, z)) in samples.iter().enumerate() {
+                // Reuse the cap contours so that the side walls share their positions
+                let contour = if s == 0 || s == samples.len() - 1 {
+                    cap_loops[ring_index].clone()
+                } else if inset == 0.0 {
+                    points.clone()
+                } else {
+                    let t = inset / cap_inset;
+                    points.iter()
+                        .zip(&cap_loops[ring_index])
+                        .map(|(outer, cap)| [outer[0] + (cap[0] - outer[0]) * t, outer[1] + (cap[1] - outer[1]) * t])
+                        .collect()
+                };
+                let v = profile_lengths[s] / profile_length;
 
-            // Bottom left (original i0)
-            vertices.push([p0[0], -p0[1], 0.0]);
-            uvs.push([u0, 0.0]);
-
-            // Bottom right (original i1)
-            vertices.push([p1[0], -p1[1], 0.0]);
-            uvs.push([u1, 0.0]);
-
-            // Top right (extruded i1)
-            vertices.push([p1[0], -p1[1], depth]);
-            uvs.push([u1, -1.0]);
+                for k in 0..count {
+                    let [x, y] = contour[k % contour.len()];
+                    vertices.push([x, y, z]);
+                    uvs.push([distances[k] / perimeter, v]);
+                }
+            }
 
-            // Top left (extruded i0)
-            vertices.push([p0[0], -p0[1], depth]);
-            uvs.push([u0, -1.0]);
+            for s in 0..samples.len() - 1 {
+                for k in 0..points.len() {
+                    let bottom0 = base_idx + s * count + k;
+                    let bottom1 = base_idx + s * count + k + 1;
+                    let top0 = base_idx + (s + 1) * count + k;
+                    let top1 = base_idx + (s + 1) * count + k + 1;
 
-            // Create two triangles for the side quad
-            // Triangle 1: bottom-left, bottom-right, top-right
-            side_indices.push([base_idx, base_idx + 1, base_idx + 2]);
-            // Triangle 2: bottom-left, top-right, top-left
-            side_indices.push([base_idx, base_idx + 2, base_idx + 3]);
+                    // Triangle 1: bottom-left, bottom-right, top-right
+                    side_indices.push([bottom0, bottom1, top1]);
+                    // Triangle 2: bottom-left, top-right, top-left
+                    side_indices.push([bottom0, top1, top0]);
+                }
+            }
         }
 
         Mesh3D {
             vertices,
             uvs,
+            normals: Vec::new(),
             faces: vec![
                 MeshGroup { indices: front_indices, name: "front" },
                 MeshGroup { indices: back_indices, name: "back" },
@@ -257,7 +575,58 @@ impl Mesh2D {
             ],
         }
     }
-    
+
+    /// Oriented contours of every ring, see `ring_loop`.
+    fn ring_loops(&self) -> Vec<Vec<[f64; 2]>> {
+        self.rings.iter()
+            .enumerate()
+            .map(|(ring_index, ring)| self.ring_loop(ring, ring_index == 0))
+            .collect()
+    }
+
+    /// Points of a ring in mesh coordinates (y up), without consecutive duplicates.
+    ///
+    /// The ring is oriented so that the solid lies on its left: counterclockwise for the
+    /// exterior ring and clockwise for the holes. Walls built along it then face away from
+    /// the solid, outward for the exterior and into the hole for the interiors.
+    fn ring_loop(&self, ring: &Range<usize>, exterior: bool) -> Vec<[f64; 2]> {
+        let mut points: Vec<[f64; 2]> = Vec::with_capacity(ring.len());
+        for [x, y] in &self.vertices[ring.clone()] {
+            let point = [*x, -*y];
+            if points.last() != Some(&point) {
+                points.push(point);
+            }
+        }
+        while points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+
+        let mut area = 0.0;
+        for k in 0..points.len() {
+            let p0 = points[k];
+            let p1 = points[(k + 1) % points.len()];
+            area += p0[0] * p1[1] - p1[0] * p0[1];
+        }
+        if (area > 0.0) != exterior {
+            points.reverse();
+        }
+
+        points
+    }
+
+    /// Sum of the signed areas of the triangles in mesh coordinates (y up).
+    fn triangulation_area(&self) -> f64 {
+        self.indices.chunks(3)
+            .map(|triangle| {
+                let [ax, ay] = self.vertices[triangle[0]];
+                let [bx, by] = self.vertices[triangle[1]];
+                let [cx, cy] = self.vertices[triangle[2]];
+                // Image y axis points down
+                -((bx - ax) * (cy - ay) - (by - ay) * (cx - ax))
+            })
+            .sum()
+    }
+
     pub fn get_vertices(&self) -> &Vec<[f64; 2]> {
         &self.vertices
     }
@@ -267,51 +636,531 @@ impl Mesh2D {
     }
 }
 
+/// Distance to the contour at the point `(x, y)` in image coordinates.
+///
+/// The distance transform is measured between pixel centers, so half a pixel is removed for
+/// the distance to vanish on the contour, which runs along pixel edges.
+fn sample_distance(distances: &ImageBuffer<Luma<f32>, Vec<f32>>, x: f64, y: f64) -> f64 {
+    (sample_bilinear(distances, x, y) - 0.5).max(0.0)
+}
+
+/// Value of a single channel image at the point `(x, y)` in image coordinates, bilinearly
+/// interpolated between pixel centers.
+fn sample_bilinear<T: Primitive>(image: &ImageBuffer<Luma<T>, Vec<T>>, x: f64, y: f64) -> f64 {
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return 0.0;
+    }
+
+    let x = (x - 0.5).clamp(0.0, (width - 1) as f64);
+    let y = (y - 0.5).clamp(0.0, (height - 1) as f64);
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (tx, ty) = (x - x0 as f64, y - y0 as f64);
+
+    let value = |px: u32, py: u32| image.get_pixel(px, py).0[0].to_f64().unwrap_or(0.0);
+    let top = value(x0, y0) * (1.0 - tx) + value(x1, y0) * tx;
+    let bottom = value(x0, y1) * (1.0 - tx) + value(x1, y1) * tx;
+    top * (1.0 - ty) + bottom * ty
+}
+
+/// Offset a contour oriented with the solid on its left toward the solid, with mitered
+/// corners. Every point is moved along the bisector of its adjacent edges so that the offset
+/// contour keeps the same number of points; the miter is limited on sharp corners.
+fn offset_loop(points: &[[f64; 2]], distance: f64) -> Vec<[f64; 2]> {
+    const MITER_LIMIT: f64 = 4.0;
+
+    let len = points.len();
+    (0..len)
+        .map(|k| {
+            let prev = points[(k + len - 1) % len];
+            let current = points[k];
+            let next = points[(k + 1) % len];
+
+            // Left normals of the incoming and outgoing edges
+            let n0 = left_normal(prev, current);
+            let n1 = left_normal(current, next);
+            let mut miter = [n0[0] + n1[0], n0[1] + n1[1]];
+            let length = (miter[0] * miter[0] + miter[1] * miter[1]).sqrt();
+            if length < 1e-9 {
+                miter = n1;
+            } else {
+                miter = [miter[0] / length, miter[1] / length];
+            }
+
+            let cos = miter[0] * n1[0] + miter[1] * n1[1];
+            let scale = if cos > 1.0 / MITER_LIMIT { distance / cos } else { distance * MITER_LIMIT };
+            [current[0] + miter[0] * scale, current[1] + miter[1] * scale]
+        })
+        .collect()
+}
+
+/// Offset contours oriented with the solid on their left toward the solid by `distance`,
+/// reducing the offset of the points where the contours would fold over.
+///
+/// Every point starts at its mitered offset, see `offset_loop`. The points of an offset edge
+/// that shrinks below a tenth of its length, reverses, or crosses another offset edge get
+/// their offset halved, until no such edge is left. Thin features thus keep a narrower
+/// inset rather than invalidating the whole polygon, and no two consecutive points are ever
+/// merged. Contours still invalid after the last pass are left to the caller to reject.
+fn inset_loops(loops: &[Vec<[f64; 2]>], distance: f64) -> Vec<Vec<[f64; 2]>> {
+    const MAX_PASSES: usize = 64;
+    const MIN_EDGE_RATIO: f64 = 0.1;
+
+    let offsets: Vec<Vec<[f64; 2]>> = loops.iter()
+        .map(|points| {
+            points.iter()
+                .zip(offset_loop(points, distance))
+                .map(|(point, offset)| [offset[0] - point[0], offset[1] - point[1]])
+                .collect()
+        })
+        .collect();
+    let mut scales: Vec<Vec<f64>> = loops.iter().map(|points| vec![1.0; points.len()]).collect();
+
+    let inset = |scales: &[Vec<f64>]| -> Vec<Vec<[f64; 2]>> {
+        loops.iter()
+            .zip(&offsets)
+            .zip(scales)
+            .map(|((points, offsets), scales)| {
+                points.iter()
+                    .zip(offsets)
+                    .zip(scales)
+                    .map(|((point, offset), scale)| [point[0] + offset[0] * scale, point[1] + offset[1] * scale])
+                    .collect()
+            })
+            .collect()
+    };
+
+    let mut pass = 0;
+    loop {
+        let insets = inset(&scales);
+
+        // Edges are identified by their ring and their first point
+        let mut folded: Vec<(usize, usize)> = Vec::new();
+        for (ring, (points, inset_points)) in loops.iter().zip(&insets).enumerate() {
+            let len = points.len();
+            for k in 0..len {
+                let (a, b) = (points[k], points[(k + 1) % len]);
+                let (c, d) = (inset_points[k], inset_points[(k + 1) % len]);
+                let edge = [b[0] - a[0], b[1] - a[1]];
+                let dot = edge[0] * (d[0] - c[0]) + edge[1] * (d[1] - c[1]);
+                if dot < MIN_EDGE_RATIO * (edge[0] * edge[0] + edge[1] * edge[1]) {
+                    folded.push((ring, k));
+                }
+            }
+        }
+        folded.extend(crossing_edges(&insets));
+
+        // Insets still folded after the last pass are returned as they are, the caller
+        // rejects them when a ring flips over or the inset polygon is invalid
+        if folded.is_empty() || pass == MAX_PASSES {
+            return insets;
+        }
+
+        for (ring, k) in folded {
+            let len = scales[ring].len();
+            scales[ring][k] *= 0.5;
+            scales[ring][(k + 1) % len] *= 0.5;
+        }
+        pass += 1;
+    }
+}
+
+/// Edges of closed contours that touch or cross an edge other than their neighbours, as
+/// pairs of ring index and index of the first point of the edge.
+fn crossing_edges(loops: &[Vec<[f64; 2]>]) -> Vec<(usize, usize)> {
+    struct Edge {
+        ring: usize,
+        index: usize,
+        a: [f64; 2],
+        b: [f64; 2],
+    }
+
+    let mut edges: Vec<Edge> = loops.iter()
+        .enumerate()
+        .flat_map(|(ring, points)| {
+            let len = points.len();
+            (0..len).map(move |index| Edge { ring, index, a: points[index], b: points[(index + 1) % len] })
+        })
+        .collect();
+    edges.sort_by(|e0, e1| e0.a[0].min(e0.b[0]).total_cmp(&e1.a[0].min(e1.b[0])));
+
+    // Sweep along x, only edges whose x ranges overlap can intersect
+    let mut crossing = Vec::new();
+    for (i, e0) in edges.iter().enumerate() {
+        let max_x = e0.a[0].max(e0.b[0]);
+        for e1 in edges[i + 1..].iter().take_while(|e1| e1.a[0].min(e1.b[0]) <= max_x) {
+            if e0.ring == e1.ring {
+                let len = loops[e0.ring].len();
+                if (e0.index + 1) % len == e1.index || (e1.index + 1) % len == e0.index {
+                    continue;
+                }
+            }
+            if segments_intersect(e0.a, e0.b, e1.a, e1.b) {
+                crossing.push((e0.ring, e0.index));
+                crossing.push((e1.ring, e1.index));
+            }
+        }
+    }
+    crossing
+}
+
+/// Whether the segments `ab` and `cd` cross or touch.
+fn segments_intersect(a: [f64; 2], b: [f64; 2], c: [f64; 2], d: [f64; 2]) -> bool {
+    let orientation = |p: [f64; 2], q: [f64; 2], r: [f64; 2]| (q[0] - p[0]) * (r[1] - p[1]) - (q[1] - p[1]) * (r[0] - p[0]);
+    let on_segment = |p: [f64; 2], q: [f64; 2], r: [f64; 2]| {
+        r[0] >= p[0].min(q[0]) && r[0] <= p[0].max(q[0]) && r[1] >= p[1].min(q[1]) && r[1] <= p[1].max(q[1])
+    };
+
+    let (o0, o1) = (orientation(a, b, c), orientation(a, b, d));
+    let (o2, o3) = (orientation(c, d, a), orientation(c, d, b));
+    if ((o0 > 0.0 && o1 < 0.0) || (o0 < 0.0 && o1 > 0.0)) && ((o2 > 0.0 && o3 < 0.0) || (o2 < 0.0 && o3 > 0.0)) {
+        return true;
+    }
+
+    (o0 == 0.0 && on_segment(a, b, c)) ||
+        (o1 == 0.0 && on_segment(a, b, d)) ||
+        (o2 == 0.0 && on_segment(c, d, a)) ||
+        (o3 == 0.0 && on_segment(c, d, b))
+}
+
+/// Signed area of a contour, positive when counter-clockwise.
+fn loop_area(points: &[[f64; 2]]) -> f64 {
+    let len = points.len();
+    (0..len)
+        .map(|k| {
+            let [x0, y0] = points[k];
+            let [x1, y1] = points[(k + 1) % len];
+            x0 * y1 - x1 * y0
+        })
+        .sum::<f64>() / 2.0
+}
+
+fn left_normal(a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length > 0.0 {
+        [-dy / length, dx / length]
+    } else {
+        [0.0, 0.0]
+    }
+}
+
+/// Non normalized face normal, its length is twice the triangle area.
+pub(crate) fn face_normal(a: [f64; 3], b: [f64; 3], c: [f64; 3]) -> [f64; 3] {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ]
+}
+
+/// Unit vector with the same direction, or the null vector if it has no length.
+fn normalize(v: [f64; 3]) -> [f64; 3] {
+    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if length > 0.0 {
+        v.map(|c| c / length)
+    } else {
+        [0.0; 3]
+    }
+}
+
+/// Angle at `p` of the triangle `(p, a, b)`.
+fn corner_angle(p: [f64; 3], a: [f64; 3], b: [f64; 3]) -> f64 {
+    let u = normalize([a[0] - p[0], a[1] - p[1], a[2] - p[2]]);
+    let v = normalize([b[0] - p[0], b[1] - p[1], b[2] - p[2]]);
+    (u[0] * v[0] + u[1] * v[1] + u[2] * v[2]).clamp(-1.0, 1.0).acos()
+}
+
+/// Hashable key of a position, used to find coincident vertices.
+pub(crate) fn position_key([x, y, z]: [f64; 3]) -> [u64; 3] {
+    [x.to_bits(), y.to_bits(), z.to_bits()]
+}
+
+/// Algorithm triangulating the faces of a polygon, see [`PolygonMesh::mesh2d_with`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Triangulator {
+    /// Ear clipping, fast but prone to long sliver triangles
+    #[default]
+    Earcut,
+    /// Constrained Delaunay triangulation, which maximizes the smallest angles of the
+    /// triangles, refined with Steiner points until they meet the quality bounds
+    Delaunay(Refinement),
+}
+
+/// Quality bounds of a constrained Delaunay triangulation, see [`Triangulator::Delaunay`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Refinement {
+    /// Smallest angle of the triangles in degrees, 0 disables angle refinement. Bounds above
+    /// 30 degrees are rarely met, and angles between the edges of the polygon are kept.
+    pub min_angle: f64,
+    /// Largest area of the triangles in square pixels
+    pub max_area: Option<f64>,
+    /// Largest number of Steiner points inserted, ten times the number of vertices of the
+    /// polygon by default
+    pub max_steiner_points: Option<usize>,
+}
+
+impl Default for Refinement {
+    fn default() -> Self {
+        Refinement {
+            min_angle: 20.0,
+            max_area: None,
+            max_steiner_points: None,
+        }
+    }
+}
+
+impl Refinement {
+    /// Whether the bounds insert any point, a plain constrained Delaunay triangulation otherwise.
+    fn is_enabled(&self) -> bool {
+        self.min_angle > 0.0 || self.max_area.is_some()
+    }
+}
+
 pub trait PolygonMesh {
+    /// Triangulate the polygon with earcut.
     fn mesh2d(&self) -> anyhow::Result<Mesh2D>;
+
+    /// Triangulate the polygon with the given algorithm.
+    ///
+    /// Refining a Delaunay triangulation inserts Steiner points inside of the polygon and on
+    /// the edges of its rings. The points splitting an edge become vertices of its ring, so
+    /// that side walls built along the rings share the edges of the faces. Fails if the rings
+    /// of the polygon intersect.
+    fn mesh2d_with(&self, triangulator: &Triangulator) -> anyhow::Result<Mesh2D>;
+
+    /// Triangulate the polygon with additional vertices on a regular grid of the given
+    /// spacing inside of it, so that the faces can be displaced, e.g. by
+    /// [`Mesh2D::inflate`]. Grid points closer than half the spacing to the contour are
+    /// skipped to avoid slivers.
+    ///
+    /// Uses a constrained Delaunay triangulation rather than earcut, which handles interior
+    /// points poorly, refined like [`PolygonMesh::mesh2d_with`] when `refinement` is given.
+    /// Fails if the rings of the polygon intersect.
+    fn mesh2d_with_interior(&self, spacing: f64, refinement: Option<&Refinement>) -> anyhow::Result<Mesh2D>;
 }
 
 impl PolygonMesh for Polygon {
 
     fn mesh2d(&self) -> anyhow::Result<Mesh2D> {
-        let mut vertices: Vec<[f64; 2]> = Vec::new();
-        let mut coords: Vec<f64> = Vec::new();
-        let mut holes: Vec<usize> = Vec::new();
+        self.mesh2d_with(&Triangulator::Earcut)
+    }
+
+    fn mesh2d_with(&self, triangulator: &Triangulator) -> anyhow::Result<Mesh2D> {
+        let refinement = match triangulator {
+            Triangulator::Earcut => return earcut_mesh(self),
+            Triangulator::Delaunay(refinement) => refinement,
+        };
+        delaunay_mesh(self, Vec::new(), Some(refinement), true)
+    }
 
-        for coord in self.exterior().points() {
-            let x = coord.x();
-            let y = coord.y();
+    fn mesh2d_with_interior(&self, spacing: f64, refinement: Option<&Refinement>) -> anyhow::Result<Mesh2D> {
+        if spacing <= 0.0 {
+            return Err(anyhow!("Interior point spacing must be positive, got {}", spacing));
+        }
+
+        // Regular grid of interior points, away from the contour
+        let mut interior: Vec<[f64; 2]> = Vec::new();
+        if let Some(bounds) = self.bounding_rect() {
+            let mut y = (bounds.min().y / spacing).floor() * spacing;
+            while y <= bounds.max().y {
+                let mut x = (bounds.min().x / spacing).floor() * spacing;
+                while x <= bounds.max().x {
+                    let point = Point::new(x, y);
+                    let inside = self.contains(&point) &&
+                        std::iter::once(self.exterior()).chain(self.interiors())
+                            .all(|ring| Euclidean.distance(&point, ring) >= spacing / 2.0);
+                    if inside {
+                        interior.push([x, y]);
+                    }
+                    x += spacing;
+                }
+                y += spacing;
+            }
+        }
+
+        delaunay_mesh(self, interior, refinement, true)
+    }
+}
 
+fn earcut_mesh(polygon: &Polygon) -> anyhow::Result<Mesh2D> {
+    let mut vertices: Vec<[f64; 2]> = Vec::new();
+    let mut coords: Vec<f64> = Vec::new();
+    let mut holes: Vec<usize> = Vec::new();
+    let mut rings: Vec<Range<usize>> = Vec::new();
+
+    for (ring_index, ring) in std::iter::once(polygon.exterior()).chain(polygon.interiors()).enumerate() {
+        if ring_index > 0 {
+            holes.push(coords.len() / 2);
+        }
+
+        let start = vertices.len();
+        for [x, y] in open_ring(ring) {
             coords.push(x);
             coords.push(y);
             vertices.push([x, y]);
         }
-        
-        for hole in self.interiors() {
-            holes.push(coords.len() / 2);
+        rings.push(start..vertices.len());
+    }
 
-            let hole_points: Vec<_> = hole.points().collect();
-            let hole_coords = if hole_points.len() > 1 &&
-                hole_points[0].x() == hole_points[hole_points.len()-1].x() &&
-                hole_points[0].y() == hole_points[hole_points.len()-1].y() {
-                &hole_points[..hole_points.len()-1]
-            } else {
-                &hole_points[..]
-            };
-            
-            for coord in hole_coords {
-                let x = coord.x();
-                let y = coord.y();
-
-                coords.push(x);
-                coords.push(y);
-                vertices.push([x, y]);
+    // Triangulate with correct winding orders
+    let indices = earcut(&coords, &holes, 2)?;
+
+    Ok(Mesh2D { vertices, indices, rings })
+}
+
+type Cdt = ConstrainedDelaunayTriangulation<Point2<f64>>;
+
+/// Largest distance between a point splitting an edge and the edge, relative to its length
+const SPLIT_TOLERANCE: f64 = 1e-9;
+
+/// Constrained Delaunay triangulation of a polygon and of additional points inside of it,
+/// refined when `refinement` is given, with Steiner points on the edges of the rings if
+/// `split_rings` is set.
+///
+/// The vertices of the mesh are the vertices of the rings, along with the points splitting
+/// their edges, followed by the interior points and the Steiner points.
+fn delaunay_mesh(polygon: &Polygon, interior: Vec<[f64; 2]>, refinement: Option<&Refinement>, split_rings: bool) -> anyhow::Result<Mesh2D> {
+    let mut triangulation = Cdt::new();
+    let insert = |triangulation: &mut Cdt, [x, y]: [f64; 2]| {
+        triangulation.insert(Point2::new(x, y))
+            .map_err(|e| anyhow!("Cannot triangulate point ({}, {}): {:?}", x, y, e))
+    };
+
+    // Coincident points are merged by spade, triangles refer to the first of them
+    let contours: Vec<Vec<[f64; 2]>> = std::iter::once(polygon.exterior()).chain(polygon.interiors())
+        .map(open_ring)
+        .collect();
+    let ring_handles = contours.iter()
+        .map(|points| points.iter().map(|&point| insert(&mut triangulation, point)).collect())
+        .collect::<anyhow::Result<Vec<Vec<_>>>>()?;
+    for &point in &interior {
+        insert(&mut triangulation, point)?;
+    }
+
+    for (points, handles) in contours.iter().zip(&ring_handles) {
+        for k in 0..handles.len() {
+            let next = handles[(k + 1) % handles.len()];
+            if handles[k] != next && triangulation.try_add_constraint(handles[k], next).is_empty() {
+                return Err(anyhow!("Polygon is self-intersecting near ({}, {})", points[k][0], points[k][1]));
+            }
+        }
+    }
+
+    // Faces outside of the polygon, the convex hull and the holes are also triangulated
+    let excluded_faces = match refinement {
+        Some(refinement) if refinement.is_enabled() => {
+            let mut parameters = RefinementParameters::new()
+                .exclude_outer_faces(true)
+                .with_angle_limit(AngleLimit::from_deg(refinement.min_angle.max(0.0)));
+            if let Some(max_area) = refinement.max_area {
+                parameters = parameters.with_max_allowed_area(max_area);
             }
+            if let Some(max_steiner_points) = refinement.max_steiner_points {
+                parameters = parameters.with_max_additional_vertices(max_steiner_points);
+            }
+            if !split_rings {
+                parameters = parameters.keep_constraint_edges();
+            }
+            Some(triangulation.refine(parameters).excluded_faces.into_iter().collect::<HashSet<_>>())
         }
+        _ => None,
+    };
 
-        // Triangulate with correct winding orders
-        let indices = earcut(&coords, &holes, 2)?;
+    let mut order: Vec<FixedVertexHandle> = Vec::new();
+    let mut rings: Vec<Range<usize>> = Vec::new();
+    for handles in &ring_handles {
+        let start = order.len();
+        for k in 0..handles.len() {
+            order.extend(constraint_path(&triangulation, handles[k], handles[(k + 1) % handles.len()]));
+        }
+        rings.push(start..order.len());
+    }
+    let mut vertex_of_handle: Vec<Option<usize>> = vec![None; triangulation.num_vertices()];
+    for (i, handle) in order.iter().enumerate() {
+        vertex_of_handle[handle.index()].get_or_insert(i);
+    }
+    for handle in triangulation.fixed_vertices() {
+        if vertex_of_handle[handle.index()].is_none() {
+            vertex_of_handle[handle.index()] = Some(order.len());
+            order.push(handle);
+        }
+    }
+    let vertices: Vec<[f64; 2]> = order.iter()
+        .map(|&handle| {
+            let position = triangulation.vertex(handle).position();
+            [position.x, position.y]
+        })
+        .collect();
+
+    let mut indices = Vec::new();
+    for face in triangulation.inner_faces() {
+        let corners = face.vertices().map(|vertex| vertex_of_handle[vertex.fix().index()].expect("every vertex of the triangulation is ordered"));
+        let inside = match &excluded_faces {
+            Some(excluded_faces) => !excluded_faces.contains(&face.fix()),
+            None => {
+                let centroid = corners.iter()
+                    .fold([0.0, 0.0], |[cx, cy], &i| [cx + vertices[i][0] / 3.0, cy + vertices[i][1] / 3.0]);
+                polygon.contains(&Point::new(centroid[0], centroid[1]))
+            }
+        };
+        if inside {
+            indices.extend(corners);
+        }
+    }
+
+    Ok(Mesh2D { vertices, indices, rings })
+}
+
+/// Vertices along the constraint edges from `from` to `to`, without `to`.
+///
+/// The edge of a ring is split where it runs through other vertices, and by the refinement.
+/// Each step follows the constraint edge to the vertex lying on the original segment, up to
+/// rounding of the split points, that comes next along it. Edges of the ring meeting at a
+/// sharp angle do not lie on the segment and are never taken.
+fn constraint_path(triangulation: &Cdt, from: FixedVertexHandle, to: FixedVertexHandle) -> Vec<FixedVertexHandle> {
+    let start = triangulation.vertex(from).position();
+    let end = triangulation.vertex(to).position();
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let length_squared = dx * dx + dy * dy;
+
+    // Position along the segment in [0, 1], if the point is on the segment
+    let along = |position: Point2<f64>| {
+        let (px, py) = (position.x - start.x, position.y - start.y);
+        let t = (px * dx + py * dy) / length_squared;
+        let on_segment = (px * dy - py * dx).abs() <= SPLIT_TOLERANCE * length_squared && t > 0.0 && t <= 1.0;
+        on_segment.then_some(t)
+    };
+
+    let mut path = vec![from];
+    let mut current = from;
+    let mut current_t = 0.0;
+    while current != to {
+        let next = triangulation.vertex(current).out_edges()
+            .filter(|edge| edge.is_constraint_edge())
+            .filter_map(|edge| along(edge.to().position()).map(|t| (edge.to().fix(), t)))
+            .filter(|&(_, t)| t > current_t)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+        match next {
+            Some((next, t)) if next != to => {
+                current = next;
+                current_t = t;
+                path.push(current);
+            }
+            _ => break,
+        }
+    }
+    path
+}
 
-        Ok(Mesh2D { vertices, indices })
+/// Points of a ring without the closing point, earcut and the side walls expect open rings.
+fn open_ring(ring: &LineString) -> Vec<[f64; 2]> {
+    let mut points: Vec<[f64; 2]> = ring.coords().map(|coord| [coord.x, coord.y]).collect();
+    if points.len() > 1 && points[0] == points[points.len() - 1] {
+        points.pop();
     }
+    points
 }
\ No newline at end of file
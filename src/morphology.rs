@@ -0,0 +1,145 @@
+use crate::binary_image::BinaryImage;
+use crate::bit_rows::BitRows;
+
+/// Neighbourhood of a pixel used by morphological operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructuringElement {
+    /// Square of side `2 * radius + 1`
+    Square(u32),
+    /// Horizontal and vertical segments of length `2 * radius + 1`
+    Cross(u32),
+    /// Pixels within the radius of the center
+    Disk(u32),
+}
+
+impl StructuringElement {
+    #[inline]
+    fn radius(&self) -> usize {
+        match *self {
+            StructuringElement::Square(radius) |
+            StructuringElement::Cross(radius) |
+            StructuringElement::Disk(radius) => radius as usize,
+        }
+    }
+
+    /// Half width of the row of the element at the vertical offset `dy` from its center.
+    fn half_width(&self, dy: usize) -> usize {
+        let radius = self.radius();
+        match self {
+            StructuringElement::Square(_) => radius,
+            StructuringElement::Cross(_) => if dy == 0 { radius } else { 0 },
+            StructuringElement::Disk(_) => ((radius * radius - dy * dy) as f64).sqrt().floor() as usize,
+        }
+    }
+}
+
+impl BinaryImage {
+    /// Shrink the foreground: a pixel stays set only if every pixel of the structuring element
+    /// centered on it is set. Pixels outside of the image are background, so the foreground
+    /// also shrinks away from the image borders.
+    pub fn erode(&self, element: StructuringElement) -> BinaryImage {
+        morphology(&BitRows::from_image(self), element, true).to_image()
+    }
+
+    /// Grow the foreground: a pixel is set if any pixel of the structuring element centered on
+    /// it is set.
+    pub fn dilate(&self, element: StructuringElement) -> BinaryImage {
+        morphology(&BitRows::from_image(self), element, false).to_image()
+    }
+
+    /// Erode then dilate, removing foreground specks smaller than the structuring element.
+    pub fn open(&self, element: StructuringElement) -> BinaryImage {
+        let eroded = morphology(&BitRows::from_image(self), element, true);
+        morphology(&eroded, element, false).to_image()
+    }
+
+    /// Dilate then erode, filling pinholes and gaps smaller than the structuring element.
+    pub fn close(&self, element: StructuringElement) -> BinaryImage {
+        let dilated = morphology(&BitRows::from_image(self), element, false);
+        morphology(&dilated, element, true).to_image()
+    }
+}
+
+/// Erode or dilate, a word at a time.
+///
+/// The element is decomposed in horizontal segments: every row of the image is combined with
+/// its horizontal shifts for growing half widths, and as soon as a half width used by the
+/// element is reached, the segment is combined into the rows of the result above and below
+/// the row at the matching offsets. Only one segment is held at a time.
+fn morphology(rows: &BitRows, element: StructuringElement, erode: bool) -> BitRows {
+    let (width, height) = (rows.width(), rows.height());
+    let radius = element.radius();
+    let combine = |accumulator: &mut u64, word: u64| {
+        if erode { *accumulator &= word } else { *accumulator |= word }
+    };
+
+    // Vertical offsets of the rows of the element, by half width
+    let mut offsets: Vec<Vec<usize>> = Vec::new();
+    for dy in 0..=radius {
+        let half_width = element.half_width(dy);
+        if offsets.len() <= half_width {
+            offsets.resize(half_width + 1, Vec::new());
+        }
+        offsets[half_width].push(dy);
+    }
+
+    // Rows outside of the image are background, erosion clears the rows reaching them
+    let mut result = BitRows::zeros(width, height);
+    if erode {
+        for y in radius..height.saturating_sub(radius) {
+            result.row_mut(y).fill(u64::MAX);
+        }
+    }
+
+    let mut segment = vec![0u64; rows.words_per_row()];
+    let mut shifted = vec![0u64; rows.words_per_row()];
+    for source in 0..height {
+        segment.copy_from_slice(rows.row(source));
+        for (half_width, offsets) in offsets.iter().enumerate() {
+            if half_width > 0 {
+                for shift in [half_width as isize, -(half_width as isize)] {
+                    shift_row(rows.row(source), shift, &mut shifted);
+                    for (word, &other) in segment.iter_mut().zip(&shifted) {
+                        combine(word, other);
+                    }
+                }
+            }
+
+            for &dy in offsets {
+                let above = source.checked_sub(dy);
+                let below = Some(source + dy).filter(|&y| dy > 0 && y < height);
+                for y in [above, below].into_iter().flatten() {
+                    for (word, &other) in result.row_mut(y).iter_mut().zip(&segment) {
+                        combine(word, other);
+                    }
+                }
+            }
+        }
+    }
+
+    let last_word_mask = rows.last_word_mask();
+    for y in 0..height {
+        if let Some(last) = result.row_mut(y).last_mut() {
+            *last &= last_word_mask;
+        }
+    }
+
+    result
+}
+
+/// Shift a row so that bit `x` of `out` is bit `x + shift` of `row`, bits shifted in are zero.
+fn shift_row(row: &[u64], shift: isize, out: &mut [u64]) {
+    let len = row.len() as isize;
+    let words = shift.div_euclid(64);
+    let bits = shift.rem_euclid(64) as u32;
+    let word = |j: isize| if (0..len).contains(&j) { row[j as usize] } else { 0 };
+
+    for (j, value) in out.iter_mut().enumerate() {
+        let source = j as isize + words;
+        *value = if bits == 0 {
+            word(source)
+        } else {
+            (word(source) >> bits) | (word(source + 1) << (64 - bits))
+        };
+    }
+}
@@ -0,0 +1,138 @@
+use bit_vec::BitVec;
+use geo::{Polygon, Translate};
+use rayon::prelude::*;
+use crate::binary_image::BinaryImage;
+use crate::components::{ComponentStats, Components};
+use crate::contour::TraceOptions;
+
+/// Horizontal run of pixels of a component, as a row and a range of columns relative to the
+/// top left corner of its bounding box
+type Run = (usize, usize, usize);
+
+impl BinaryImage {
+    /// Parallel [`BinaryImage::trace_polygons`].
+    pub fn par_trace_polygons(&self, min_dimension: usize) -> Vec<Polygon> {
+        self.par_trace_polygons_with(&TraceOptions {
+            min_dimension,
+            min_hole_dimension: min_dimension,
+            ..Default::default()
+        })
+    }
+
+    /// Parallel [`BinaryImage::trace_polygons_with`], returning the same polygons in the same
+    /// order.
+    ///
+    /// The components of the foreground are labelled first, then each of them is copied
+    /// alone into its bounding box and traced on the rayon thread pool. The rings of a
+    /// component only depend on its own pixels, and its polygon comes in the order of its
+    /// first pixel either way. Components inside of a hole filtered out by `options` are
+    /// dropped like the sequential tracer does, which takes labelling the background too
+    /// when there is a dimension filter.
+    pub fn par_trace_polygons_with(&self, options: &TraceOptions) -> Vec<Polygon> {
+        let filtered = self.without_small_regions(options);
+        let image = filtered.as_ref().unwrap_or(self);
+
+        let components = image.label_components(options.connectivity);
+        let kept = if options.min_dimension > 0 || options.min_hole_dimension > 0 {
+            kept_components(image, &components, options)
+        } else {
+            vec![true; components.len()]
+        };
+
+        runs(&components)
+            .into_par_iter()
+            .zip(components.stats())
+            .zip(kept)
+            .filter_map(|((runs, stats), kept)| {
+                if !kept {
+                    return None;
+                }
+
+                // The crop holds a single component, so it traces to a single polygon
+                let polygon = crop(stats, &runs)
+                    .trace(options)
+                    .to_polygons()
+                    .pop()?;
+                Some(polygon.translate(stats.x as f64, stats.y as f64))
+            })
+            .collect()
+    }
+}
+
+/// Runs of pixels of every component, in the order of their labels.
+fn runs(components: &Components) -> Vec<Vec<Run>> {
+    let labels = components.labels();
+    let mut runs: Vec<Vec<Run>> = vec![Vec::new(); components.len()];
+    for (y, row) in labels.rows().enumerate() {
+        let mut start = 0;
+        let mut previous = 0;
+        for (x, label) in row.map(|pixel| pixel.0[0]).chain([0]).enumerate() {
+            if label == previous {
+                continue;
+            }
+            if previous != 0 {
+                let stats = &components.stats()[previous as usize - 1];
+                let (left, top) = (stats.x as usize, stats.y as usize);
+                runs[previous as usize - 1].push((y - top, start - left, x - left));
+            }
+            start = x;
+            previous = label;
+        }
+    }
+    runs
+}
+
+/// Image of the bounding box of a component, holding only its pixels.
+fn crop(stats: &ComponentStats, runs: &[Run]) -> BinaryImage {
+    let width = stats.width as usize;
+    let mut buffer = BitVec::from_elem(width * stats.height as usize, false);
+    for &(y, start, end) in runs {
+        for x in start..end {
+            buffer.set(y * width + x, true);
+        }
+    }
+    BinaryImage::from_bitvec(stats.width, stats.height, buffer)
+}
+
+/// Whether every component is traced by the sequential tracer with the dimension filters of
+/// `options`.
+///
+/// A component is kept when it is large enough and the hole it lies in, if any, is kept. The
+/// hole is bounded by the background region on the left of the first pixel of the component,
+/// and a hole is kept when it is large enough and the component above its first pixel, that
+/// bounds it, is kept. Both come before the component in scan order.
+fn kept_components(image: &BinaryImage, components: &Components, options: &TraceOptions) -> Vec<bool> {
+    let background = image.invert().label_components(options.connectivity.dual());
+    let (width, height) = (image.width(), image.height());
+    let large_enough = |stats: &ComponentStats, min_dimension: usize| {
+        stats.width as usize >= min_dimension && stats.height as usize >= min_dimension
+    };
+    let enclosed = |stats: &ComponentStats| {
+        stats.x > 0 && stats.y > 0 && stats.x + stats.width < width && stats.y + stats.height < height
+    };
+
+    let mut kept: Vec<bool> = Vec::with_capacity(components.len());
+    for stats in components.stats() {
+        let (x, y) = first_pixel(components, stats);
+        let hole = (x > 0)
+            .then(|| background.labels().get_pixel(x - 1, y).0[0])
+            .map(|label| &background.stats()[label as usize - 1])
+            .filter(|hole| enclosed(hole));
+
+        let hole_kept = hole.is_none_or(|hole| {
+            let (x, y) = first_pixel(&background, hole);
+            let owner = components.labels().get_pixel(x, y - 1).0[0];
+            large_enough(hole, options.min_hole_dimension) && kept[owner as usize - 1]
+        });
+        kept.push(hole_kept && large_enough(stats, options.min_dimension));
+    }
+    kept
+}
+
+/// First pixel of a component in scan order, on the top row of its bounding box.
+fn first_pixel(components: &Components, stats: &ComponentStats) -> (u32, u32) {
+    let x = (stats.x..stats.x + stats.width)
+        .find(|&x| components.labels().get_pixel(x, stats.y).0[0] == stats.label)
+        .expect("Components have a pixel on the top row of their bounding box");
+    (x, stats.y)
+}
@@ -47,6 +47,7 @@ impl DivAssign for Bit {
 
 impl Add for Bit {
     type Output = Self;
+    #[allow(clippy::suspicious_arithmetic_impl)]
     fn add(mut self, rhs: Self) -> Self::Output {
         self.0 |= *rhs;
         self
@@ -55,6 +56,7 @@ impl Add for Bit {
 
 impl Sub for Bit {
     type Output = Self;
+    #[allow(clippy::suspicious_arithmetic_impl)]
     fn sub(mut self, rhs: Self) -> Self::Output {
         self.0 ^= *rhs;
         self
@@ -63,6 +65,7 @@ impl Sub for Bit {
 
 impl Mul for Bit {
     type Output = Self;
+    #[allow(clippy::suspicious_arithmetic_impl)]
     fn mul(mut self, rhs: Self) -> Self::Output {
         self.0 &= *rhs;
         self
@@ -0,0 +1,136 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use anyhow::anyhow;
+use image::RgbaImage;
+use crate::mesh::Mesh3D;
+
+/// PLY file encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlyFormat {
+    /// Human-readable ASCII PLY
+    Ascii,
+    /// Binary little-endian PLY
+    BinaryLittleEndian,
+}
+
+impl Mesh3D {
+    /// Export the mesh as a Stanford PLY file.
+    ///
+    /// Vertices carry their position and their texture coordinates as `s`/`t` properties.
+    /// When a texture is given, an RGBA color is added to every vertex by sampling the texture
+    /// at the pixel under the vertex. Faces carry a `group` property holding the index of their
+    /// face group and a `group_name` list property holding the bytes of its name. Fails if
+    /// there are more than 256 face groups or a name longer than 255 bytes.
+    pub fn export_ply(&self, path: &Path, format: PlyFormat, texture: Option<&RgbaImage>) -> anyhow::Result<()> {
+        // Group indices and name lengths are written as uchar
+        if self.faces.len() > usize::from(u8::MAX) + 1 {
+            return Err(anyhow!("PLY export supports at most 256 face groups, got {}", self.faces.len()));
+        }
+        if let Some(group) = self.faces.iter().find(|group| group.name.len() > usize::from(u8::MAX)) {
+            return Err(anyhow!("Face group name {} is longer than 255 bytes", group.name));
+        }
+
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+
+        let face_count: usize = self.faces.iter().map(|group| group.indices.len()).sum();
+
+        // Header
+        writeln!(writer, "ply")?;
+        match format {
+            PlyFormat::Ascii => writeln!(writer, "format ascii 1.0")?,
+            PlyFormat::BinaryLittleEndian => writeln!(writer, "format binary_little_endian 1.0")?,
+        }
+        writeln!(writer, "comment generated by mimesis")?;
+        writeln!(writer, "element vertex {}", self.vertices.len())?;
+        writeln!(writer, "property float x")?;
+        writeln!(writer, "property float y")?;
+        writeln!(writer, "property float z")?;
+        writeln!(writer, "property float s")?;
+        writeln!(writer, "property float t")?;
+        if texture.is_some() {
+            writeln!(writer, "property uchar red")?;
+            writeln!(writer, "property uchar green")?;
+            writeln!(writer, "property uchar blue")?;
+            writeln!(writer, "property uchar alpha")?;
+        }
+        writeln!(writer, "element face {}", face_count)?;
+        writeln!(writer, "property list uchar int vertex_indices")?;
+        writeln!(writer, "property uchar group")?;
+        writeln!(writer, "property list uchar uchar group_name")?;
+        writeln!(writer, "end_header")?;
+
+        // Vertices
+        for (i, [x, y, z]) in self.vertices.iter().enumerate() {
+            let [s, t] = self.uvs.get(i).copied().unwrap_or([0.0, 0.0]);
+            let color = texture.map(|texture| sample_texture(texture, *x, -*y));
+
+            match format {
+                PlyFormat::Ascii => {
+                    write!(writer, "{} {} {} {} {}", *x as f32, *y as f32, *z as f32, s as f32, t as f32)?;
+                    if let Some([r, g, b, a]) = color {
+                        write!(writer, " {} {} {} {}", r, g, b, a)?;
+                    }
+                    writeln!(writer)?;
+                }
+                PlyFormat::BinaryLittleEndian => {
+                    for value in [*x, *y, *z, s, t] {
+                        writer.write_all(&(value as f32).to_le_bytes())?;
+                    }
+                    if let Some(color) = color {
+                        writer.write_all(&color)?;
+                    }
+                }
+            }
+        }
+
+        // Faces
+        for (group_index, group) in self.faces.iter().enumerate() {
+            let group_index = group_index as u8;
+            let name = group.name.as_bytes();
+            let ascii_name: Vec<String> = std::iter::once(name.len() as u8).chain(name.iter().copied())
+                .map(|byte| byte.to_string())
+                .collect();
+            for [i0, i1, i2] in &group.indices {
+                match format {
+                    PlyFormat::Ascii => {
+                        writeln!(writer, "3 {} {} {} {} {}", i0, i1, i2, group_index, ascii_name.join(" "))?;
+                    }
+                    PlyFormat::BinaryLittleEndian => {
+                        writer.write_all(&[3u8])?;
+                        for index in [*i0, *i1, *i2] {
+                            writer.write_all(&(index as i32).to_le_bytes())?;
+                        }
+                        writer.write_all(&[group_index, name.len() as u8])?;
+                        writer.write_all(name)?;
+                    }
+                }
+            }
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Color of the texture at the point `(x, y)` in image coordinates.
+///
+/// Contour vertices lie on pixel corners, so the most opaque of the pixels touching the point
+/// is used to avoid picking the transparent background around the silhouette.
+fn sample_texture(texture: &RgbaImage, x: f64, y: f64) -> [u8; 4] {
+    let (width, height) = texture.dimensions();
+    if width == 0 || height == 0 {
+        return [0, 0, 0, 0];
+    }
+
+    let clamp = |value: f64, size: u32| (value.max(0.0) as u32).min(size - 1);
+    let (x0, x1) = (clamp((x - 0.5).floor(), width), clamp((x + 0.5).floor(), width));
+    let (y0, y1) = (clamp((y - 0.5).floor(), height), clamp((y + 0.5).floor(), height));
+
+    [(x0, y0), (x1, y0), (x0, y1), (x1, y1)]
+        .iter()
+        .map(|&(px, py)| texture.get_pixel(px, py).0)
+        .max_by_key(|pixel| pixel[3])
+        .unwrap_or([0, 0, 0, 0])
+}
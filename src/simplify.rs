@@ -0,0 +1,393 @@
+use geo::{BoundingRect, Coord, Distance, Euclidean, Line, LineString, Polygon};
+
+/// How much [`simplify_polygons`] simplifies the polygons
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SimplifyTarget {
+    /// Tolerance in pixels, the same for every ring
+    Tolerance(f64),
+    /// Tolerance as a fraction of the diagonal of the bounding box of each polygon, so that
+    /// polygons keep the same shape whatever the resolution of the image
+    RelativeTolerance(f64),
+    /// Maximum number of vertices of each polygon
+    PolygonBudget(usize),
+    /// Maximum number of vertices of all of the polygons together
+    TotalBudget(usize),
+}
+
+/// Polygon simplified by [`simplify_polygon`], along with the rings it lost
+#[derive(Debug, Clone)]
+pub struct Simplified {
+    /// Simplified polygon, `None` if its exterior degenerated
+    pub polygon: Option<Polygon>,
+    /// Indices of the holes of the original polygon that degenerated and were dropped. The
+    /// holes of a degenerate exterior are not listed.
+    pub dropped_holes: Vec<usize>,
+    /// Tolerance every ring of the simplified polygon was simplified with, exterior first
+    pub tolerances: Vec<f64>,
+}
+
+/// Simplify the rings of a polygon with Ramer-Douglas-Peucker, keeping the polygon valid.
+///
+/// Every ring is simplified like [`geo::Simplify`] does, then the spans where rings cross
+/// each other or themselves, or where a hole leaves the exterior or swallows another hole,
+/// are refined with the original vertex farthest from them until the polygon is valid again.
+/// Rings that the tolerance leaves with fewer than three vertices degenerate: they are
+/// dropped and reported instead of being kept as slivers.
+pub fn simplify_polygon(polygon: &Polygon, tolerance: f64) -> Simplified {
+    let tolerances = vec![tolerance; 1 + polygon.interiors().len()];
+    simplify_rings(polygon, &tolerances)
+}
+
+/// Simplify polygons like [`simplify_polygon`], choosing the tolerance of every ring from
+/// `target`.
+///
+/// Vertex budgets are split across the rings in proportion to their perimeter, and every
+/// ring takes the smallest tolerance leaving it within its share. Rings whose share is
+/// less than three vertices degenerate, and the refinements keeping polygons valid may
+/// exceed the budget.
+pub fn simplify_polygons(polygons: &[Polygon], target: SimplifyTarget) -> Vec<Simplified> {
+    let tolerances: Vec<Vec<f64>> = match target {
+        SimplifyTarget::Tolerance(tolerance) => polygons.iter()
+            .map(|polygon| vec![tolerance; rings(polygon).len()])
+            .collect(),
+        SimplifyTarget::RelativeTolerance(fraction) => polygons.iter()
+            .map(|polygon| {
+                let diagonal = polygon.bounding_rect()
+                    .map(|rect| rect.width().hypot(rect.height()))
+                    .unwrap_or(0.0);
+                vec![fraction * diagonal; rings(polygon).len()]
+            })
+            .collect(),
+        SimplifyTarget::PolygonBudget(budget) => polygons.iter()
+            .map(|polygon| budget_tolerances(&rings(polygon), budget))
+            .collect(),
+        SimplifyTarget::TotalBudget(budget) => {
+            let all: Vec<&LineString> = polygons.iter().flat_map(rings).collect();
+            let mut tolerances = budget_tolerances(&all, budget).into_iter();
+            polygons.iter()
+                .map(|polygon| tolerances.by_ref().take(rings(polygon).len()).collect())
+                .collect()
+        }
+    };
+
+    polygons.iter()
+        .zip(tolerances)
+        .map(|(polygon, tolerances)| simplify_rings(polygon, &tolerances))
+        .collect()
+}
+
+/// Exterior and holes of a polygon.
+fn rings(polygon: &Polygon) -> Vec<&LineString> {
+    std::iter::once(polygon.exterior()).chain(polygon.interiors()).collect()
+}
+
+/// Simplify every ring of a polygon with its own tolerance, exterior first.
+fn simplify_rings(polygon: &Polygon, tolerances: &[f64]) -> Simplified {
+    let exterior = SimplifiedRing::new(polygon.exterior(), tolerances[0]);
+    if exterior.degenerate() {
+        return Simplified { polygon: None, dropped_holes: Vec::new(), tolerances: Vec::new() };
+    }
+
+    let mut rings = vec![exterior];
+    let mut dropped_holes = Vec::new();
+    let mut kept_tolerances = vec![tolerances[0]];
+    for (i, (hole, &tolerance)) in polygon.interiors().iter().zip(&tolerances[1..]).enumerate() {
+        let hole = SimplifiedRing::new(hole, tolerance);
+        if hole.degenerate() {
+            dropped_holes.push(i);
+        } else {
+            rings.push(hole);
+            kept_tolerances.push(tolerance);
+        }
+    }
+
+    // Every round refines at least one span, at worst until the rings are back to their
+    // originals. Spans are refined from the last so that the earlier ones keep their index.
+    loop {
+        let spans = conflicting_spans(&rings);
+        let mut refined = false;
+        for (ring, span) in spans.into_iter().rev() {
+            refined |= rings[ring].refine(span);
+        }
+        if !refined {
+            break;
+        }
+    }
+
+    let mut rings = rings.into_iter().map(SimplifiedRing::into_line_string);
+    let exterior = rings.next().expect("The exterior is kept");
+    Simplified {
+        polygon: Some(Polygon::new(exterior, rings.collect())),
+        dropped_holes,
+        tolerances: kept_tolerances,
+    }
+}
+
+/// Tolerance of every ring sharing a vertex budget in proportion to their perimeter.
+fn budget_tolerances(rings: &[&LineString], budget: usize) -> Vec<f64> {
+    let perimeters: Vec<f64> = rings.iter()
+        .map(|ring| ring.lines().map(|line| line.dx().hypot(line.dy())).sum())
+        .collect();
+    let total: f64 = perimeters.iter().sum();
+
+    rings.iter()
+        .zip(perimeters)
+        .map(|(ring, perimeter)| {
+            let share = if total > 0.0 { (budget as f64 * perimeter / total).floor() as usize } else { 0 };
+            budget_tolerance(&ring.0, share)
+        })
+        .collect()
+}
+
+/// Smallest tolerance leaving at most `budget` vertices to a closed ring.
+///
+/// A vertex is kept by Ramer-Douglas-Peucker when its distance to the span it splits, and
+/// the distances of the vertices splitting the spans around it, are all above the tolerance.
+/// The smallest of those distances is its significance, and the budget is met by the
+/// tolerance equal to the significance of the first vertex left out. The first vertex
+/// always stays.
+fn budget_tolerance(coords: &[Coord], budget: usize) -> f64 {
+    if coords.len() < 2 {
+        return 0.0;
+    }
+
+    let mut significances = Vec::with_capacity(coords.len());
+    let mut spans = vec![(0, coords.len() - 1, f64::INFINITY)];
+    while let Some((first, last, bound)) = spans.pop() {
+        if let Some((index, distance)) = farthest(coords, first, last) {
+            let significance = distance.min(bound);
+            significances.push(significance);
+            spans.push((first, index, significance));
+            spans.push((index, last, significance));
+        }
+    }
+
+    let others = budget.saturating_sub(1);
+    if significances.len() <= others {
+        return 0.0;
+    }
+    significances.sort_unstable_by(|a, b| b.total_cmp(a));
+    significances[others]
+}
+
+/// Ring along with the indices of the vertices kept by the simplification.
+struct SimplifiedRing<'a> {
+    /// Closed original ring
+    coords: &'a [Coord],
+    /// Sorted indices of the kept vertices, starting at the first vertex and ending at the
+    /// last one, which closes the ring
+    kept: Vec<usize>,
+}
+
+impl<'a> SimplifiedRing<'a> {
+    fn new(ring: &'a LineString, tolerance: f64) -> Self {
+        let coords = ring.0.as_slice();
+        if tolerance <= 0.0 {
+            return SimplifiedRing { coords, kept: (0..coords.len()).collect() };
+        }
+
+        let mut kept = Vec::new();
+        if coords.len() >= 2 {
+            let mut spans = vec![(0, coords.len() - 1)];
+            kept.push(0);
+            while let Some((first, last)) = spans.pop() {
+                match farthest(coords, first, last) {
+                    Some((index, distance)) if distance > tolerance => {
+                        kept.push(index);
+                        spans.push((first, index));
+                        spans.push((index, last));
+                    }
+                    _ => (),
+                }
+            }
+            kept.push(coords.len() - 1);
+            kept.sort_unstable();
+        }
+        SimplifiedRing { coords, kept }
+    }
+
+    /// Whether fewer than three distinct vertices are left, not enough to bound any area.
+    fn degenerate(&self) -> bool {
+        let mut distinct: Vec<Coord> = Vec::with_capacity(3);
+        for &index in &self.kept {
+            let coord = self.coords[index];
+            if !distinct.contains(&coord) {
+                distinct.push(coord);
+                if distinct.len() == 3 {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Segment of the simplified ring from the kept vertex `span` to the next one.
+    fn segment(&self, span: usize) -> Line {
+        Line::new(self.coords[self.kept[span]], self.coords[self.kept[span + 1]])
+    }
+
+    fn spans(&self) -> usize {
+        self.kept.len() - 1
+    }
+
+    /// Keep the original vertex of a span farthest from its segment, false if the span has
+    /// none left to restore.
+    fn refine(&mut self, span: usize) -> bool {
+        match farthest(self.coords, self.kept[span], self.kept[span + 1]) {
+            Some((index, _)) => {
+                self.kept.insert(span + 1, index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether a point lies inside of the simplified ring, `None` if it lies on it.
+    fn contains(&self, point: Coord) -> Option<bool> {
+        let vertices = self.kept.iter().map(|&index| self.coords[index]);
+        ring_contains(vertices, point)
+    }
+
+    /// Spans whose original vertices loop around a point, which the simplification moved to
+    /// the other side of the ring.
+    fn spans_around(&self, point: Coord) -> Vec<usize> {
+        (0..self.spans())
+            .filter(|&span| {
+                let vertices = self.coords[self.kept[span]..=self.kept[span + 1]].iter().copied();
+                ring_contains(vertices, point) == Some(true)
+            })
+            .collect()
+    }
+
+    fn into_line_string(self) -> LineString {
+        LineString::new(self.kept.iter().map(|&index| self.coords[index]).collect())
+    }
+}
+
+/// Vertex strictly between `first` and `last` farthest from the line between them, the last
+/// one of equal distance like [`geo::Simplify`].
+fn farthest(coords: &[Coord], first: usize, last: usize) -> Option<(usize, f64)> {
+    let line = Line::new(coords[first], coords[last]);
+    (first + 1..last)
+        .map(|index| (index, Euclidean.distance(coords[index], &line)))
+        .fold(None, |farthest, (index, distance)| match farthest {
+            Some((_, farthest_distance)) if distance < farthest_distance => farthest,
+            _ => Some((index, distance)),
+        })
+}
+
+/// Spans of the rings to refine, as the indices of their ring and of their first vertex.
+fn conflicting_spans(rings: &[SimplifiedRing]) -> Vec<(usize, usize)> {
+    let mut conflicts = Vec::new();
+
+    // Segments sorted by their left end, each compared to the next ones overlapping it
+    let mut segments: Vec<(usize, usize, Line)> = rings.iter()
+        .enumerate()
+        .flat_map(|(ring, simplified)| {
+            (0..simplified.spans()).map(move |span| (ring, span, simplified.segment(span)))
+        })
+        .collect();
+    segments.sort_by(|a, b| left(&a.2).total_cmp(&left(&b.2)));
+    for (i, &(ring, span, segment)) in segments.iter().enumerate() {
+        let right = segment.start.x.max(segment.end.x);
+        for &(other_ring, other_span, other) in &segments[i + 1..] {
+            if left(&other) > right {
+                break;
+            }
+            if segments_conflict(segment, other) {
+                conflicts.push((ring, span));
+                conflicts.push((other_ring, other_span));
+            }
+        }
+    }
+    if !conflicts.is_empty() {
+        conflicts.sort_unstable();
+        conflicts.dedup();
+        return conflicts;
+    }
+
+    // Without crossings, a hole is either completely inside of another ring or outside of it
+    let point = |ring: &SimplifiedRing| {
+        ring.kept.iter()
+            .map(|&index| ring.coords[index])
+            .find(|&coord| rings[0].contains(coord).is_some())
+    };
+    for (hole, simplified) in rings.iter().enumerate().skip(1) {
+        let Some(point) = point(simplified) else {
+            continue;
+        };
+        if rings[0].contains(point) == Some(false) {
+            conflicts.extend(rings[0].spans_around(point).into_iter().map(|span| (0, span)));
+        }
+        for (other, other_simplified) in rings.iter().enumerate().skip(1) {
+            if other != hole && other_simplified.contains(point) == Some(true) {
+                conflicts.extend(other_simplified.spans_around(point).into_iter().map(|span| (other, span)));
+            }
+        }
+    }
+    conflicts.sort_unstable();
+    conflicts.dedup();
+    conflicts
+}
+
+fn left(line: &Line) -> f64 {
+    line.start.x.min(line.end.x)
+}
+
+/// Whether two segments share any point other than a common end, or overlap along a line.
+fn segments_conflict(a: Line, b: Line) -> bool {
+    let (o1, o2) = (orientation(a.start, a.end, b.start), orientation(a.start, a.end, b.end));
+    let (o3, o4) = (orientation(b.start, b.end, a.start), orientation(b.start, b.end, a.end));
+
+    let shared = a.start == b.start || a.start == b.end || a.end == b.start || a.end == b.end;
+    if shared {
+        // Segments meeting at an end only conflict when they double back on each other
+        if o1 != 0.0 || o2 != 0.0 {
+            return false;
+        }
+        return [(b.start, a), (b.end, a), (a.start, b), (a.end, b)]
+            .iter()
+            .any(|&(point, line)| point != line.start && point != line.end && on_segment(point, line));
+    }
+
+    if o1 * o2 < 0.0 && o3 * o4 < 0.0 {
+        return true;
+    }
+    (o1 == 0.0 && on_segment(b.start, a)) ||
+        (o2 == 0.0 && on_segment(b.end, a)) ||
+        (o3 == 0.0 && on_segment(a.start, b)) ||
+        (o4 == 0.0 && on_segment(a.end, b))
+}
+
+/// Twice the signed area of the triangle `a`, `b`, `c`.
+fn orientation(a: Coord, b: Coord, c: Coord) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+/// Whether a point collinear with a segment lies on it.
+fn on_segment(point: Coord, line: Line) -> bool {
+    point.x >= line.start.x.min(line.end.x) && point.x <= line.start.x.max(line.end.x) &&
+        point.y >= line.start.y.min(line.end.y) && point.y <= line.start.y.max(line.end.y)
+}
+
+/// Even-odd test of a point against the closed ring through `vertices`, `None` if the point
+/// lies on the ring.
+fn ring_contains(vertices: impl Iterator<Item = Coord> + Clone, point: Coord) -> Option<bool> {
+    let mut inside = false;
+    let first = vertices.clone().next()?;
+    let mut previous = first;
+    for vertex in vertices.skip(1).chain(std::iter::once(first)) {
+        let line = Line::new(previous, vertex);
+        if orientation(previous, vertex, point) == 0.0 && on_segment(point, line) {
+            return None;
+        }
+        if (previous.y > point.y) != (vertex.y > point.y) {
+            let x = previous.x + (point.y - previous.y) / (vertex.y - previous.y) * (vertex.x - previous.x);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+        previous = vertex;
+    }
+    Some(inside)
+}
@@ -0,0 +1,196 @@
+use geo::{Coord, LineString, Polygon};
+
+/// Curve fitted through the vertices between the corners of a ring, see [`smooth_polygon`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SmoothCurve {
+    /// Centripetal Catmull-Rom spline, passing through every vertex
+    #[default]
+    CatmullRom,
+    /// Uniform cubic B-spline, approaching the vertices without passing through them
+    BSpline,
+}
+
+/// Options of [`smooth_polygon`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SmoothOptions {
+    pub curve: SmoothCurve,
+    /// Turn in degrees above which a vertex is a corner, kept in place with sharp edges
+    pub corner_angle: f64,
+    /// Length in pixels of the segments the curves are resampled to
+    pub segment_length: f64,
+}
+
+impl Default for SmoothOptions {
+    fn default() -> Self {
+        SmoothOptions {
+            curve: SmoothCurve::CatmullRom,
+            corner_angle: 60.0,
+            segment_length: 4.0,
+        }
+    }
+}
+
+/// Smooth the rings of a polygon while keeping their corners.
+///
+/// The vertices where a ring turns by more than the corner angle stay fixed, and the runs
+/// of vertices between them are replaced by a curve fitted through them and resampled to
+/// the segment length. Runs between two adjacent corners are straight and kept as they
+/// are. Unlike Chaikin smoothing, tips stay sharp, and a Catmull-Rom curve does not shrink
+/// the shape since it passes through the original vertices.
+///
+/// Contours traced along pixel edges turn by right angles at every vertex, so they should
+/// be simplified first, otherwise every vertex is a corner and the polygon is unchanged.
+pub fn smooth_polygon(polygon: &Polygon, options: &SmoothOptions) -> Polygon {
+    Polygon::new(
+        smooth_ring(polygon.exterior(), options),
+        polygon.interiors().iter().map(|ring| smooth_ring(ring, options)).collect(),
+    )
+}
+
+fn smooth_ring(ring: &LineString, options: &SmoothOptions) -> LineString {
+    let mut points = ring.0.clone();
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    let n = points.len();
+    if n < 3 || options.segment_length <= 0.0 {
+        return ring.clone();
+    }
+
+    let corners: Vec<usize> = (0..n)
+        .filter(|&i| turn(points[(i + n - 1) % n], points[i], points[(i + 1) % n]) > options.corner_angle)
+        .collect();
+
+    let smoothed = if corners.is_empty() {
+        // A closed curve needs three segments to remain a ring, whatever its length
+        let mut closed = resample(&dense_curve(&points, true, options), options.segment_length, 3);
+        closed.pop();
+        closed
+    } else {
+        // Runs from every corner to the next one, sharing their corners
+        let mut smoothed = Vec::new();
+        for (k, &corner) in corners.iter().enumerate() {
+            let next = corners[(k + 1) % corners.len()];
+            let length = (next + n - corner - 1) % n + 1;
+            let run: Vec<Coord> = (0..=length).map(|i| points[(corner + i) % n]).collect();
+            let curve = if run.len() == 2 {
+                run
+            } else {
+                resample(&dense_curve(&run, false, options), options.segment_length, 1)
+            };
+            smoothed.extend_from_slice(&curve[..curve.len() - 1]);
+        }
+        smoothed
+    };
+
+    let mut ring = LineString::new(smoothed);
+    ring.close();
+    ring
+}
+
+/// Angle in degrees between the directions of the segments before and after a vertex.
+pub(crate) fn turn(previous: Coord, vertex: Coord, next: Coord) -> f64 {
+    let (a, b) = (vertex - previous, next - vertex);
+    if a == Coord::zero() || b == Coord::zero() {
+        return 0.0;
+    }
+    let cross = a.x * b.y - a.y * b.x;
+    let dot = a.x * b.x + a.y * b.y;
+    cross.atan2(dot).abs().to_degrees()
+}
+
+/// Points of the curve through `points` on every span, finely enough to be resampled. Open
+/// curves start and end on their first and last points.
+fn dense_curve(points: &[Coord], closed: bool, options: &SmoothOptions) -> Vec<Coord> {
+    let n = points.len();
+    let control: Vec<Coord> = match (closed, options.curve) {
+        (true, _) => [&points[n - 1..], points, &points[..2]].concat(),
+        // Reflect the neighbours of the ends, so that the curve leaves them straight
+        (false, SmoothCurve::CatmullRom) => [
+            &[points[0] * 2.0 - points[1]][..],
+            points,
+            &[points[n - 1] * 2.0 - points[n - 2]][..],
+        ].concat(),
+        // Repeat the ends three times, so that the curve passes through them
+        (false, SmoothCurve::BSpline) => [&[points[0]; 2][..], points, &[points[n - 1]; 2][..]].concat(),
+    };
+
+    let mut dense = Vec::new();
+    for span in control.windows(4) {
+        let [p0, p1, p2, p3] = [span[0], span[1], span[2], span[3]];
+        let chord = (p2 - p1).x.hypot((p2 - p1).y);
+        let samples = ((4.0 * chord / options.segment_length).ceil() as usize).max(8);
+        for sample in 0..samples {
+            let t = sample as f64 / samples as f64;
+            dense.push(match options.curve {
+                SmoothCurve::CatmullRom => catmull_rom(p0, p1, p2, p3, t),
+                SmoothCurve::BSpline => b_spline(p0, p1, p2, p3, t),
+            });
+        }
+    }
+
+    if closed {
+        dense.push(dense[0]);
+    } else {
+        // Exactly on the corners, which the blended points only approach up to rounding
+        dense[0] = points[0];
+        dense.push(points[n - 1]);
+    }
+    dense
+}
+
+/// Point of the centripetal Catmull-Rom span from `p1` to `p2`.
+fn catmull_rom(p0: Coord, p1: Coord, p2: Coord, p3: Coord, t: f64) -> Coord {
+    // Knots spaced by the square root of the distances, falling back to uniform spacing
+    // between coincident points
+    let interval = |a: Coord, b: Coord| {
+        let dt = (b - a).x.hypot((b - a).y).sqrt();
+        if dt > 1e-9 { dt } else { 1.0 }
+    };
+    let t1 = interval(p0, p1);
+    let t2 = t1 + interval(p1, p2);
+    let t3 = t2 + interval(p2, p3);
+    let t = t1 + t * (t2 - t1);
+
+    let lerp = |a: Coord, b: Coord, ta: f64, tb: f64| a * ((tb - t) / (tb - ta)) + b * ((t - ta) / (tb - ta));
+    let a1 = lerp(p0, p1, 0.0, t1);
+    let a2 = lerp(p1, p2, t1, t2);
+    let a3 = lerp(p2, p3, t2, t3);
+    let b1 = lerp(a1, a2, 0.0, t2);
+    let b2 = lerp(a2, a3, t1, t3);
+    lerp(b1, b2, t1, t2)
+}
+
+/// Point of the uniform cubic B-spline span controlled by four points.
+fn b_spline(p0: Coord, p1: Coord, p2: Coord, p3: Coord, t: f64) -> Coord {
+    let (t2, t3) = (t * t, t * t * t);
+    let b0 = (1.0 - t).powi(3) / 6.0;
+    let b1 = (3.0 * t3 - 6.0 * t2 + 4.0) / 6.0;
+    let b2 = (-3.0 * t3 + 3.0 * t2 + 3.0 * t + 1.0) / 6.0;
+    let b3 = t3 / 6.0;
+    p0 * b0 + p1 * b1 + p2 * b2 + p3 * b3
+}
+
+/// Points along a polyline spaced as close as possible to `segment_length`, keeping its ends,
+/// split into at least `min_segments` segments.
+fn resample(polyline: &[Coord], segment_length: f64, min_segments: usize) -> Vec<Coord> {
+    let length = |a: Coord, b: Coord| (b - a).x.hypot((b - a).y);
+    let total: f64 = polyline.windows(2).map(|pair| length(pair[0], pair[1])).sum();
+    let segments = ((total / segment_length).round() as usize).max(min_segments);
+    let spacing = total / segments as f64;
+
+    let mut resampled = vec![polyline[0]];
+    let mut walked = 0.0;
+    let mut next = spacing;
+    for pair in polyline.windows(2) {
+        let step = length(pair[0], pair[1]);
+        while resampled.len() < segments && next <= walked + step {
+            let t = if step > 0.0 { (next - walked) / step } else { 0.0 };
+            resampled.push(pair[0] + (pair[1] - pair[0]) * t);
+            next += spacing;
+        }
+        walked += step;
+    }
+    resampled.push(polyline[polyline.len() - 1]);
+    resampled
+}
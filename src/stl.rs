@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use anyhow::anyhow;
+use crate::mesh::{face_normal, position_key, Mesh3D};
+
+/// STL file encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StlFormat {
+    /// Compact binary STL, supported by every slicer
+    Binary,
+    /// Human-readable ASCII STL
+    Ascii,
+}
+
+impl Mesh3D {
+    /// Export the mesh as an STL solid for 3D printing.
+    ///
+    /// Every coordinate is multiplied by `scale`, e.g. the size of a pixel in millimetres.
+    /// The mesh must be watertight and consistently wound, see [`Mesh3D::check_watertight`].
+    pub fn export_stl(&self, path: &Path, format: StlFormat, scale: f64) -> anyhow::Result<()> {
+        self.check_watertight()?;
+
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+
+        let triangles = self.faces.iter().flat_map(|group| group.indices.iter());
+        let triangle_count: usize = self.faces.iter().map(|group| group.indices.len()).sum();
+
+        match format {
+            StlFormat::Binary => {
+                let mut header = [0u8; 80];
+                let title = b"mimesis binary STL";
+                header[..title.len()].copy_from_slice(title);
+                writer.write_all(&header)?;
+                writer.write_all(&(triangle_count as u32).to_le_bytes())?;
+
+                for &[i0, i1, i2] in triangles {
+                    let (normal, corners) = self.facet(i0, i1, i2, scale);
+                    for value in normal.iter().chain(corners.iter().flatten()) {
+                        writer.write_all(&(*value as f32).to_le_bytes())?;
+                    }
+                    // Attribute byte count
+                    writer.write_all(&0u16.to_le_bytes())?;
+                }
+            }
+            StlFormat::Ascii => {
+                writeln!(writer, "solid Mesh3D")?;
+                for &[i0, i1, i2] in triangles {
+                    let (normal, corners) = self.facet(i0, i1, i2, scale);
+                    writeln!(writer, "  facet normal {:e} {:e} {:e}", normal[0], normal[1], normal[2])?;
+                    writeln!(writer, "    outer loop")?;
+                    for [x, y, z] in corners {
+                        writeln!(writer, "      vertex {:e} {:e} {:e}", x, y, z)?;
+                    }
+                    writeln!(writer, "    endloop")?;
+                    writeln!(writer, "  endfacet")?;
+                }
+                writeln!(writer, "endsolid Mesh3D")?;
+            }
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Check that the mesh encloses a solid: once vertices sharing a position are welded,
+    /// every edge must be traversed as many times in both directions by the triangles.
+    ///
+    /// Face groups do not share vertices, so the check is performed on positions rather than
+    /// on vertex indices. An edge may be shared by more than two triangles, as happens on the
+    /// side walls of a contour that touches itself at a vertex, as long as they pair up with
+    /// opposite orientations.
+    pub fn check_watertight(&self) -> anyhow::Result<()> {
+        let mut welded: HashMap<[u64; 3], usize> = HashMap::new();
+        let positions: Vec<usize> = self.vertices.iter()
+            .map(|&vertex| {
+                let next = welded.len();
+                *welded.entry(position_key(vertex)).or_insert(next)
+            })
+            .collect();
+
+        // Number of times each directed edge is traversed, along with the vertex indices of
+        // the first traversal for the error messages
+        let mut edges: HashMap<(usize, usize), (usize, [usize; 2])> = HashMap::new();
+        for [i0, i1, i2] in self.faces.iter().flat_map(|group| group.indices.iter()) {
+            let (p0, p1, p2) = (positions[*i0], positions[*i1], positions[*i2]);
+            if p0 == p1 || p1 == p2 || p2 == p0 {
+                return Err(anyhow!("Mesh has a degenerate triangle at vertex {}", i0));
+            }
+            for (edge, indices) in [((p0, p1), [*i0, *i1]), ((p1, p2), [*i1, *i2]), ((p2, p0), [*i2, *i0])] {
+                edges.entry(edge).or_insert((0, indices)).0 += 1;
+            }
+        }
+
+        for (&(a, b), &(count, [i0, i1])) in &edges {
+            let opposite = edges.get(&(b, a)).map_or(0, |&(count, _)| count);
+            if opposite == 0 {
+                return Err(anyhow!("Mesh is not watertight: edge {}-{} lies on an open boundary", i0, i1));
+            }
+            if count != opposite {
+                return Err(anyhow!("Mesh is not consistently wound: edge {}-{} is traversed {} times in one direction and {} times in the other", i0, i1, count, opposite));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Unit normal and scaled corners of a triangle.
+    fn facet(&self, i0: usize, i1: usize, i2: usize, scale: f64) -> ([f64; 3], [[f64; 3]; 3]) {
+        let corners = [self.vertices[i0], self.vertices[i1], self.vertices[i2]]
+            .map(|[x, y, z]| [x * scale, y * scale, z * scale]);
+
+        let mut normal = face_normal(corners[0], corners[1], corners[2]);
+        let length = (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt();
+        if length > 0.0 {
+            normal = normal.map(|c| c / length);
+        }
+
+        (normal, corners)
+    }
+}
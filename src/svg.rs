@@ -0,0 +1,96 @@
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use geo::{Coord, LineString, Polygon};
+use crate::bezier::{BezierPolygon, CubicBezier};
+
+/// Outline that can be written as an SVG path, see [`export_svg`]
+pub trait SvgPath {
+    /// Path data of the outline, as the `d` attribute of an SVG path, with a closed subpath
+    /// for each ring.
+    fn svg_path(&self) -> String;
+}
+
+impl SvgPath for Polygon {
+    fn svg_path(&self) -> String {
+        let mut path = String::new();
+        for ring in std::iter::once(self.exterior()).chain(self.interiors()) {
+            write_ring(&mut path, ring);
+        }
+        path.trim_end().to_string()
+    }
+}
+
+impl SvgPath for BezierPolygon {
+    fn svg_path(&self) -> String {
+        let mut path = String::new();
+        for ring in std::iter::once(&self.exterior).chain(&self.interiors) {
+            write_curves(&mut path, ring);
+        }
+        path.trim_end().to_string()
+    }
+}
+
+fn write_ring(path: &mut String, ring: &LineString) {
+    let mut coords = ring.0.as_slice();
+    if coords.len() > 1 && coords.first() == coords.last() {
+        coords = &coords[..coords.len() - 1];
+    }
+    let Some((first, rest)) = coords.split_first() else {
+        return;
+    };
+    let _ = write!(path, "M{}", point(*first));
+    for &coord in rest {
+        let _ = write!(path, " L{}", point(coord));
+    }
+    path.push_str(" Z ");
+}
+
+fn write_curves(path: &mut String, curves: &[CubicBezier]) {
+    let Some(first) = curves.first() else {
+        return;
+    };
+    let _ = write!(path, "M{}", point(first.start));
+    for curve in curves {
+        let _ = write!(path, " C{} {} {}", point(curve.control1), point(curve.control2), point(curve.end));
+    }
+    path.push_str(" Z ");
+}
+
+/// Coordinates of a point, rounded to a thousandth of a pixel without trailing zeros.
+fn point(coord: Coord) -> String {
+    format!("{},{}", number(coord.x), number(coord.y))
+}
+
+fn number(value: f64) -> String {
+    let rounded = format!("{:.3}", value);
+    let trimmed = rounded.trim_end_matches('0').trim_end_matches('.');
+    match trimmed {
+        "-0" => "0".to_string(),
+        _ => trimmed.to_string(),
+    }
+}
+
+/// Export outlines as the paths of an SVG image of `width` × `height` pixels.
+///
+/// Coordinates are in pixels of the traced image, which shares the downward y axis of SVG.
+/// Every outline is written as a single path filled with the even-odd rule, so that its
+/// holes are cut out whichever way its rings are wound.
+pub fn export_svg<P: SvgPath>(path: &Path, width: u32, height: u32, outlines: &[P]) -> anyhow::Result<()> {
+    let file = File::create(path)?;
+    let mut writer = BufWriter::new(file);
+
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    )?;
+    for outline in outlines {
+        writeln!(writer, r#"  <path fill="black" fill-rule="evenodd" d="{}"/>"#, outline.svg_path())?;
+    }
+    writeln!(writer, "</svg>")?;
+
+    writer.flush()?;
+    Ok(())
+}
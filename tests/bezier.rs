@@ -0,0 +1,75 @@
+use geo::{Coord, LineString, Polygon};
+use mimesis::{fit_bezier, CubicBezier};
+
+/// Wavy circle around a five-pointed star hole, with smooth runs and sharp corners.
+fn flower() -> Polygon {
+    let exterior: Vec<(f64, f64)> = (0..120)
+        .map(|k| {
+            let angle = k as f64 * std::f64::consts::TAU / 120.0;
+            let radius = 100.0 + 8.0 * (6.0 * angle).sin();
+            (120.0 + radius * angle.cos(), 120.0 + radius * angle.sin())
+        })
+        .collect();
+    let star: Vec<(f64, f64)> = (0..10)
+        .map(|k| {
+            let angle = k as f64 * std::f64::consts::TAU / 10.0;
+            let radius = if k % 2 == 0 { 50.0 } else { 20.0 };
+            (120.0 + radius * angle.cos(), 120.0 + radius * angle.sin())
+        })
+        .collect();
+    Polygon::new(LineString::from(exterior), vec![LineString::from(star)])
+}
+
+/// Distance between a point and a curve, approximated by a fine polyline along it.
+fn distance(point: Coord, curves: &[CubicBezier]) -> f64 {
+    let samples: Vec<Coord> = curves.iter()
+        .flat_map(|curve| (0..=256).map(move |i| curve.point(i as f64 / 256.0)))
+        .collect();
+    samples.windows(2)
+        .map(|pair| {
+            let (a, b) = (pair[0], pair[1]);
+            let segment = b - a;
+            let length_squared = segment.x * segment.x + segment.y * segment.y;
+            let t = if length_squared > 0.0 {
+                (((point - a).x * segment.x + (point - a).y * segment.y) / length_squared).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let nearest = a + segment * t;
+            (point - nearest).x.hypot((point - nearest).y)
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+#[test]
+fn fitted_curves_pass_within_max_error() {
+    let polygon = flower();
+    for max_error in [0.25, 1.0, 4.0] {
+        let fitted = fit_bezier(&polygon, max_error, 60.0);
+        assert_eq!(fitted.interiors.len(), 1);
+
+        let rings = std::iter::once(polygon.exterior()).chain(polygon.interiors());
+        let curves = std::iter::once(&fitted.exterior).chain(&fitted.interiors);
+        for (ring, curves) in rings.zip(curves) {
+            assert!(!curves.is_empty());
+            for (previous, next) in curves.iter().zip(curves.iter().cycle().skip(1)) {
+                assert_eq!(previous.end, next.start);
+            }
+            for &coord in &ring.0 {
+                let distance = distance(coord, curves);
+                assert!(distance <= max_error + 1e-3, "{:?} is {} away from the curves fitted within {}", coord, distance, max_error);
+            }
+        }
+    }
+}
+
+#[test]
+fn corners_are_curve_ends() {
+    let polygon = flower();
+    let fitted = fit_bezier(&polygon, 1.0, 60.0);
+
+    // The tips and the inner corners of the star all turn by more than 60 degrees
+    for &corner in &polygon.interiors()[0].0 {
+        assert!(fitted.interiors[0].iter().any(|curve| curve.start == corner), "{:?} is not a curve end", corner);
+    }
+}
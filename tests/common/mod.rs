@@ -0,0 +1,33 @@
+// Every test crate compiles this module but only uses some of its helpers
+#![allow(dead_code)]
+
+use mimesis::BinaryImage;
+
+/// Mask drawn with `#` for the foreground and `.` for the background.
+pub fn mask(rows: &[&str]) -> BinaryImage {
+    let width = rows[0].len() as u32;
+    let height = rows.len() as u32;
+    let pixels: Vec<u8> = rows.iter()
+        .flat_map(|row| row.bytes().map(|pixel| u8::from(pixel == b'#')))
+        .collect();
+    BinaryImage::from_raw(width, height, &pixels)
+}
+
+/// Xorshift generator, so that the masks are the same on every run.
+pub struct Random(pub u64);
+
+impl Random {
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn mask(&mut self, width: u32, height: u32, density: u64) -> BinaryImage {
+        let pixels: Vec<u8> = (0..width * height)
+            .map(|_| u8::from(self.next() % 100 < density))
+            .collect();
+        BinaryImage::from_raw(width, height, &pixels)
+    }
+}
@@ -0,0 +1,140 @@
+use geo::{Area, Polygon};
+use mimesis::{BinaryImage, ComponentStats, Connectivity, TraceOptions};
+
+mod common;
+use common::mask;
+
+fn pixel_stats(label: u32, x: u32, y: u32) -> ComponentStats {
+    ComponentStats {
+        label,
+        area: 1,
+        x,
+        y,
+        width: 1,
+        height: 1,
+        centroid: (x as f64 + 0.5, y as f64 + 0.5),
+    }
+}
+
+const CHECKERBOARD: [&str; 4] = [
+    "#.#.",
+    ".#.#",
+    "#.#.",
+    ".#.#",
+];
+
+const ANTI_DIAGONAL: [&str; 5] = [
+    "....#",
+    "...#.",
+    "..#..",
+    ".#...",
+    "#....",
+];
+
+#[test]
+fn checkerboard_components() {
+    let image = mask(&CHECKERBOARD);
+
+    // Every pixel on its own, numbered in scan order
+    let components = image.label_components(Connectivity::Four);
+    let pixels = [(0, 0), (2, 0), (1, 1), (3, 1), (0, 2), (2, 2), (1, 3), (3, 3)];
+    let expected: Vec<ComponentStats> = pixels.iter().enumerate()
+        .map(|(i, &(x, y))| pixel_stats(i as u32 + 1, x, y))
+        .collect();
+    assert_eq!(components.stats(), expected.as_slice());
+    for stats in components.stats() {
+        assert_eq!(components.labels().get_pixel(stats.x, stats.y).0[0], stats.label);
+    }
+
+    let components = image.label_components(Connectivity::Eight);
+    assert_eq!(components.stats(), &[ComponentStats {
+        label: 1,
+        area: 8,
+        x: 0,
+        y: 0,
+        width: 4,
+        height: 4,
+        centroid: (2.0, 2.0),
+    }]);
+}
+
+#[test]
+fn anti_diagonal_components() {
+    let image = mask(&ANTI_DIAGONAL);
+
+    // Pixels touching by a corner only stay apart
+    let components = image.label_components(Connectivity::Four);
+    let expected: Vec<ComponentStats> = (0..5)
+        .map(|y| pixel_stats(y + 1, 4 - y, y))
+        .collect();
+    assert_eq!(components.stats(), expected.as_slice());
+
+    let components = image.label_components(Connectivity::Eight);
+    assert_eq!(components.stats(), &[ComponentStats {
+        label: 1,
+        area: 5,
+        x: 0,
+        y: 0,
+        width: 5,
+        height: 5,
+        centroid: (2.5, 2.5),
+    }]);
+    for (x, y, label) in components.labels().enumerate_pixels() {
+        assert_eq!(label.0[0] == 1, *image.get_pixel(x, y));
+    }
+}
+
+#[test]
+fn runs_merged_below_share_a_label() {
+    // Two columns only joined by the last row
+    let image = mask(&[
+        "#..#",
+        "#..#",
+        "####",
+    ]);
+    for connectivity in [Connectivity::Four, Connectivity::Eight] {
+        let components = image.label_components(connectivity);
+        assert_eq!(components.stats(), &[ComponentStats {
+            label: 1,
+            area: 8,
+            x: 0,
+            y: 0,
+            width: 4,
+            height: 3,
+            centroid: (2.0, 1.75),
+        }]);
+        assert!(components.labels().iter().all(|&label| label <= 1));
+    }
+}
+
+#[test]
+fn holes_are_filled_up_to_their_area() {
+    // Holes of 3, 4 and 5 pixels, and a one pixel notch open on the image border
+    let image = mask(&[
+        "################",
+        "#...#..#.....###",
+        "#####..#########",
+        "###############.",
+        "################",
+    ]);
+    let filled = |image: &BinaryImage| [(1, 1), (5, 2), (8, 1), (15, 3)].map(|(x, y)| *image.get_pixel(x, y));
+
+    assert_eq!(filled(&image.fill_holes(Some(3), Connectivity::Four)), [true, false, false, false]);
+    assert_eq!(filled(&image.fill_holes(Some(4), Connectivity::Four)), [true, true, false, false]);
+    assert_eq!(filled(&image.fill_holes(Some(5), Connectivity::Four)), [true, true, true, false]);
+    assert_eq!(filled(&image.fill_holes(None, Connectivity::Four)), [true, true, true, false]);
+
+    // Holes smaller than the minimum area are filled before tracing
+    let hole_areas = |min_hole_area: usize| {
+        let mut areas: Vec<f64> = image.trace_polygons_with(&TraceOptions { min_hole_area, ..Default::default() })
+            .iter()
+            .flat_map(|polygon| polygon.interiors().iter().map(|hole| Polygon::new(hole.clone(), vec![]).unsigned_area()))
+            .collect();
+        areas.sort_by(f64::total_cmp);
+        areas
+    };
+    assert_eq!(hole_areas(0), [3.0, 4.0, 5.0]);
+    assert_eq!(hole_areas(4), [4.0, 5.0]);
+    assert_eq!(hole_areas(5), [5.0]);
+    assert_eq!(hole_areas(6), [] as [f64; 0]);
+}
@@ -0,0 +1,332 @@
+use geo::{Area, BoundingRect, Contains, Polygon};
+use image::GrayImage;
+use mimesis::{trace_iso_polygons, BinaryImage, Connectivity, TraceOptions};
+
+mod common;
+use common::mask;
+
+fn trace(image: &BinaryImage, connectivity: Connectivity) -> Vec<Polygon> {
+    image.trace_polygons_with(&TraceOptions {
+        connectivity,
+        ..Default::default()
+    })
+}
+
+fn area(polygons: &[Polygon]) -> f64 {
+    polygons.iter().map(|polygon| polygon.unsigned_area()).sum()
+}
+
+fn holes(polygons: &[Polygon]) -> usize {
+    polygons.iter().map(|polygon| polygon.interiors().len()).sum()
+}
+
+const CHECKERBOARD: [&str; 4] = [
+    "#.#.",
+    ".#.#",
+    "#.#.",
+    ".#.#",
+];
+
+const DIAGONAL: [&str; 5] = [
+    "#....",
+    ".#...",
+    "..#..",
+    "...#.",
+    "....#",
+];
+
+const ANTI_DIAGONAL: [&str; 5] = [
+    "....#",
+    "...#.",
+    "..#..",
+    ".#...",
+    "#....",
+];
+
+const DIAMOND: [&str; 3] = [
+    ".#.",
+    "#.#",
+    ".#.",
+];
+
+#[test]
+fn single_pixel_is_unit_square() {
+    for connectivity in [Connectivity::Four, Connectivity::Eight] {
+        let polygons = trace(&mask(&["...", ".#.", "..."]), connectivity);
+        assert_eq!(polygons.len(), 1);
+
+        // The contour runs along the pixel edges, around its four corners
+        let mut corners: Vec<(f64, f64)> = polygons[0].exterior().points().map(|point| point.x_y()).collect();
+        assert_eq!(corners.first(), corners.last());
+        corners.pop();
+        corners.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(corners, [(1.0, 1.0), (1.0, 2.0), (2.0, 1.0), (2.0, 2.0)]);
+        assert_eq!(polygons[0].unsigned_area(), 1.0);
+    }
+}
+
+#[test]
+fn checkerboard_four_connected() {
+    let polygons = trace(&mask(&CHECKERBOARD), Connectivity::Four);
+    assert_eq!(polygons.len(), 8);
+    for polygon in &polygons {
+        // A closed unit square
+        assert_eq!(polygon.exterior().0.len(), 5);
+        assert_eq!(polygon.unsigned_area(), 1.0);
+        assert!(polygon.interiors().is_empty());
+    }
+}
+
+#[test]
+fn checkerboard_eight_connected() {
+    let polygons = trace(&mask(&CHECKERBOARD), Connectivity::Eight);
+    assert_eq!(polygons.len(), 1);
+    assert_eq!(area(&polygons), 8.0);
+
+    // The background is 4-connected, enclosing the two pixels away from the border
+    assert_eq!(holes(&polygons), 2);
+}
+
+#[test]
+fn diagonal_four_connected() {
+    for rows in [DIAGONAL, ANTI_DIAGONAL] {
+        let polygons = trace(&mask(&rows), Connectivity::Four);
+        assert_eq!(polygons.len(), 5);
+        assert_eq!(area(&polygons), 5.0);
+        assert_eq!(holes(&polygons), 0);
+    }
+}
+
+#[test]
+fn diagonal_eight_connected() {
+    for rows in [DIAGONAL, ANTI_DIAGONAL] {
+        let polygons = trace(&mask(&rows), Connectivity::Eight);
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(area(&polygons), 5.0);
+        assert_eq!(holes(&polygons), 0);
+    }
+}
+
+#[test]
+fn diamond_hole_follows_background_connectivity() {
+    let image = mask(&DIAMOND);
+
+    // The center leaks out through the diagonal gaps of an 8-connected background
+    let polygons = trace(&image, Connectivity::Four);
+    assert_eq!(polygons.len(), 4);
+    assert_eq!(holes(&polygons), 0);
+
+    let polygons = trace(&image, Connectivity::Eight);
+    assert_eq!(polygons.len(), 1);
+    assert_eq!(holes(&polygons), 1);
+    assert_eq!(area(&polygons), 4.0);
+}
+
+#[test]
+fn filled_holes_follow_connectivity() {
+    // The background inside of the ring leaks out between its diagonal pixels when the
+    // foreground is 4-connected
+    let image = mask(&[
+        "..#..",
+        ".#.#.",
+        "#...#",
+        ".#.#.",
+        "..#..",
+    ]);
+    let count = |image: &BinaryImage| (0..5).flat_map(|y| (0..5).map(move |x| (x, y)))
+        .filter(|&(x, y)| *image.get_pixel(x, y))
+        .count();
+
+    let filled = image.fill_holes(None, Connectivity::Four);
+    assert_eq!(count(&filled), 8);
+    assert_eq!(trace(&filled, Connectivity::Four), trace(&image, Connectivity::Four));
+
+    let filled = image.fill_holes(None, Connectivity::Eight);
+    assert_eq!(count(&filled), 13);
+    assert_eq!(holes(&trace(&image, Connectivity::Eight)), 1);
+    assert_eq!(holes(&trace(&filled, Connectivity::Eight)), 0);
+}
+
+#[test]
+fn hierarchy_nests_diagonal_holes() {
+    let tree = mask(&DIAMOND).trace_hierarchy(&TraceOptions {
+        connectivity: Connectivity::Eight,
+        ..Default::default()
+    });
+    assert_eq!(tree.len(), 2);
+    assert_eq!(tree.roots().count(), 1);
+
+    let hole = &tree.nodes()[1];
+    assert!(hole.is_hole());
+    assert_eq!(hole.parent(), Some(0));
+    assert_eq!(hole.depth(), 1);
+}
+
+#[test]
+fn hierarchy_nests_concentric_rings() {
+    let image = mask(&[
+        "###########",
+        "#.........#",
+        "#.#######.#",
+        "#.#.....#.#",
+        "#.#.###.#.#",
+        "#.#.###.#.#",
+        "#.#.###.#.#",
+        "#.#.....#.#",
+        "#.#######.#",
+        "#.........#",
+        "###########",
+    ]);
+    let tree = image.trace_hierarchy(&TraceOptions::default());
+    assert_eq!(tree.len(), 5);
+    assert_eq!(tree.roots().count(), 1);
+
+    // Every ring is one pixel inside of its parent, so the depth follows from the width
+    let nodes = tree.nodes();
+    let mut by_depth = vec![None; 5];
+    for (i, node) in nodes.iter().enumerate() {
+        let rect = node.ring().bounding_rect().unwrap();
+        let depth = (11 - rect.width() as usize) / 2;
+        assert_eq!(node.depth(), depth);
+        assert_eq!(node.is_hole(), depth % 2 == 1);
+        by_depth[depth] = Some(i);
+    }
+    let by_depth: Vec<usize> = by_depth.into_iter().map(Option::unwrap).collect();
+    for depth in 0..5 {
+        let node = &nodes[by_depth[depth]];
+        assert_eq!(node.parent(), depth.checked_sub(1).map(|parent| by_depth[parent]));
+        assert_eq!(node.children(), by_depth.get(depth + 1).map(std::slice::from_ref).unwrap_or(&[]));
+    }
+
+    // The island inside of the innermost hole is a polygon of its own
+    let mut polygons = tree.to_polygons();
+    polygons.sort_by(|a, b| b.unsigned_area().total_cmp(&a.unsigned_area()));
+    let areas: Vec<f64> = polygons.iter().map(|polygon| polygon.unsigned_area()).collect();
+    let hole_counts: Vec<usize> = polygons.iter().map(|polygon| polygon.interiors().len()).collect();
+    assert_eq!(areas, [40.0, 24.0, 9.0]);
+    assert_eq!(hole_counts, [1, 1, 0]);
+}
+
+/// Grayscale image drawn with `#` for 255 and `.` for 0.
+fn gray(rows: &[&str]) -> GrayImage {
+    let mut image = GrayImage::new(rows[0].len() as u32, rows.len() as u32);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, pixel) in row.bytes().enumerate() {
+            image.put_pixel(x as u32, y as u32, [if pixel == b'#' { 255 } else { 0 }].into());
+        }
+    }
+    image
+}
+
+#[test]
+fn iso_polygons_follow_connectivity() {
+    let image = gray(&CHECKERBOARD);
+    for (connectivity, count) in [(Connectivity::Four, 8), (Connectivity::Eight, 1)] {
+        let options = TraceOptions { connectivity, ..Default::default() };
+        assert_eq!(trace_iso_polygons(&image, 127.5, &options).len(), count);
+    }
+}
+
+#[test]
+fn iso_polygons_interpolate_vertices() {
+    // Columns of 0, 50, 100, 150 and 200, crossing 60 a fifth of the way from 50 to 100
+    let image = GrayImage::from_fn(5, 3, |x, _| [(x * 50) as u8].into());
+    let polygons = trace_iso_polygons(&image, 60.0, &TraceOptions::default());
+    assert_eq!(polygons.len(), 1);
+    assert!(polygons[0].interiors().is_empty());
+
+    // Pixel centers are at half coordinates, the contour runs along the image border and
+    // cuts the corner cells diagonally
+    let mut vertices: Vec<(f64, f64)> = polygons[0].exterior().coords()
+        .skip(1)
+        .map(|coord| (coord.x, coord.y))
+        .collect();
+    vertices.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let expected = [(1.7, 0.5), (1.7, 2.5), (2.5, 0.0), (2.5, 3.0), (4.5, 0.0), (4.5, 3.0), (5.0, 0.5), (5.0, 2.5)];
+    assert_eq!(vertices.len(), expected.len());
+    for ((x, y), (ex, ey)) in vertices.iter().zip(expected) {
+        assert!((x - ex).abs() < 1e-9 && (y - ey).abs() < 1e-9, "({}, {}) instead of ({}, {})", x, y, ex, ey);
+    }
+}
+
+#[test]
+fn iso_saddles_follow_the_cell_mean() {
+    // The mean of the saddle is above, at and below the level of 100
+    for (high, low, four, eight) in [(255, 60, 1, 1), (200, 0, 2, 1), (150, 0, 2, 2)] {
+        let image = GrayImage::from_fn(2, 2, |x, y| [if x == y { high } else { low }].into());
+        for (connectivity, count) in [(Connectivity::Four, four), (Connectivity::Eight, eight)] {
+            let options = TraceOptions { connectivity, ..Default::default() };
+            assert_eq!(trace_iso_polygons(&image, 100.0, &options).len(), count, "{} and {} with {:?}", high, low, connectivity);
+        }
+    }
+}
+
+#[test]
+fn iso_island_in_hole_is_its_own_polygon() {
+    let image = gray(&[
+        ".........",
+        ".#######.",
+        ".#.....#.",
+        ".#.###.#.",
+        ".#.#.#.#.",
+        ".#.###.#.",
+        ".#.....#.",
+        ".#######.",
+        ".........",
+    ]);
+    let polygons = trace_iso_polygons(&image, 127.5, &TraceOptions::default());
+    assert_eq!(polygons.len(), 2);
+    assert_eq!(polygons.iter().map(|polygon| polygon.interiors().len()).collect::<Vec<_>>(), [1, 1]);
+
+    // The outer frame comes first, and the island lies inside of its hole
+    let frame = Polygon::new(polygons[0].interiors()[0].clone(), vec![]);
+    assert!(frame.contains(polygons[1].exterior()));
+    assert!(area(&polygons[..1]) > area(&polygons[1..]));
+
+    // Filtering out a ring drops the rings it encloses
+    let options = TraceOptions { min_dimension: 6, ..Default::default() };
+    assert_eq!(trace_iso_polygons(&image, 127.5, &options).len(), 1);
+    let options = TraceOptions { min_hole_dimension: 6, ..Default::default() };
+    let polygons = trace_iso_polygons(&image, 127.5, &options);
+    assert_eq!(polygons.len(), 1);
+    assert!(polygons[0].interiors().is_empty());
+
+    // Filtering out the hole of the island keeps the island
+    let options = TraceOptions { min_hole_dimension: 2, ..Default::default() };
+    let polygons = trace_iso_polygons(&image, 127.5, &options);
+    assert_eq!(polygons.iter().map(|polygon| polygon.interiors().len()).collect::<Vec<_>>(), [1, 0]);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel_matches_sequential() {
+    // Nested rings, a small island in a hole, and components touching diagonally
+    let image = mask(&[
+        "##########.....#",
+        "#........#....#.",
+        "#.######.#...#..",
+        "#.#....#.#..#...",
+        "#.#.##.#.#.#....",
+        "#.#....#.#......",
+        "#.######.#.###..",
+        "#........#.#.#..",
+        "##########.###..",
+        "......#.........",
+        ".....#.#....##..",
+        "......#.....##..",
+    ]);
+
+    for connectivity in [Connectivity::Four, Connectivity::Eight] {
+        for min_dimension in 0..4 {
+            for min_hole_dimension in 0..4 {
+                let options = TraceOptions {
+                    connectivity,
+                    min_dimension,
+                    min_hole_dimension,
+                    ..Default::default()
+                };
+                assert_eq!(image.par_trace_polygons_with(&options), image.trace_polygons_with(&options));
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use mimesis::{BinaryImage, Connectivity, TraceOptions};
+
+/// System allocator keeping track of the peak of the bytes allocated.
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Bytes allocated at most while running `f`, on top of what was allocated before.
+fn peak_allocation<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    drop(f());
+    PEAK.load(Ordering::Relaxed) - before
+}
+
+#[test]
+fn tracing_allocates_less_than_half_a_byte_per_pixel() {
+    let (width, height) = (2000u32, 1500u32);
+    let radius = 700i64;
+    let pixels: Vec<u8> = (0..width * height)
+        .map(|i| {
+            let (dx, dy) = ((i % width) as i64 - 1000, (i / width) as i64 - 750);
+            u8::from(dx * dx + dy * dy < radius * radius)
+        })
+        .collect();
+    let image = BinaryImage::from_raw(width, height, &pixels);
+    drop(pixels);
+
+    // A padded byte per pixel, as the previous pixel grid of the tracer took
+    let baseline = (width as usize + 2) * (height as usize + 2);
+    for connectivity in [Connectivity::Four, Connectivity::Eight] {
+        let options = TraceOptions { connectivity, ..Default::default() };
+        let peak = peak_allocation(|| image.trace_hierarchy(&options));
+        assert!(peak < baseline / 2, "{:?}: {} bytes allocated for {} pixels", connectivity, peak, baseline);
+    }
+}
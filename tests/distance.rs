@@ -0,0 +1,106 @@
+use mimesis::BinaryImage;
+
+mod common;
+use common::{mask, Random};
+
+/// Distance between the center of every pixel equal to `foreground` and the center of the
+/// nearest pixel that is not, by trying them all. Pixels outside of the image are background
+/// when `outside` is set, and are ignored otherwise.
+fn reference(image: &BinaryImage, foreground: bool, outside: bool) -> Vec<f64> {
+    let (width, height) = (image.width() as i64, image.height() as i64);
+    let pixel = |x: i64, y: i64| {
+        if (0..width).contains(&x) && (0..height).contains(&y) {
+            Some(*image.get_pixel(x as u32, y as u32))
+        } else if outside {
+            Some(false)
+        } else {
+            None
+        }
+    };
+
+    let mut result = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            if pixel(x, y) != Some(foreground) {
+                result.push(0.0);
+                continue;
+            }
+            let nearest = (-1..=height)
+                .flat_map(|qy| (-1..=width).map(move |qx| (qx, qy)))
+                .filter(|&(qx, qy)| pixel(qx, qy) == Some(!foreground))
+                .map(|(qx, qy)| (((qx - x).pow(2) + (qy - y).pow(2)) as f64).sqrt())
+                .fold(f64::INFINITY, f64::min);
+            result.push(nearest);
+        }
+    }
+    result
+}
+
+fn values(image: &BinaryImage, distances: &image::ImageBuffer<image::Luma<f32>, Vec<f32>>) -> Vec<f64> {
+    assert_eq!(distances.dimensions(), (image.width(), image.height()));
+    distances.pixels().map(|pixel| pixel.0[0] as f64).collect()
+}
+
+fn assert_close(actual: &[f64], expected: &[f64], what: &str) {
+    for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+        assert!((a - e).abs() < 1e-4, "{} differs at pixel {}: {} instead of {}", what, i, a, e);
+    }
+}
+
+#[test]
+fn distance_transform_matches_reference() {
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+
+    let mut images = vec![
+        BinaryImage::from_raw(6, 4, &[0u8; 24]),
+        BinaryImage::from_raw(6, 4, &[1u8; 24]),
+        BinaryImage::from_raw(1, 1, &[1u8]),
+    ];
+    for (width, height) in [(1, 9), (9, 1), (7, 5), (16, 16), (33, 12)] {
+        for density in [10, 50, 90, 100] {
+            for _ in 0..20 {
+                images.push(random.mask(width, height, density));
+            }
+        }
+    }
+
+    for image in &images {
+        let size = format!("{}x{}", image.width(), image.height());
+        let expected = reference(image, true, true);
+        assert_close(&values(image, &image.distance_transform()), &expected, &format!("distance transform on {}", size));
+
+        // Background pixels measure the distance to the foreground inside of the image only
+        let spread = 100.0;
+        let outside = reference(image, false, false);
+        let signed: Vec<f64> = expected.iter()
+            .zip(&outside)
+            .map(|(&inside, &outside)| if inside > 0.0 { inside - 0.5 } else { 0.5 - outside })
+            .map(|distance| distance.clamp(-spread, spread))
+            .collect();
+        let field = image.signed_distance_field(spread as f32);
+        assert_close(&values(image, field.distances()), &signed, &format!("signed distance field on {}", size));
+    }
+}
+
+#[test]
+fn signed_distance_field_is_half_a_pixel_from_the_edge() {
+    let image = mask(&[
+        ".....",
+        ".###.",
+        ".###.",
+        ".###.",
+        ".....",
+    ]);
+    let field = image.signed_distance_field(2.0);
+    let distance = |x: u32, y: u32| field.distances().get_pixel(x, y).0[0];
+
+    assert_eq!(distance(2, 2), 1.5);
+    assert_eq!(distance(1, 2), 0.5);
+    assert_eq!(distance(0, 2), -0.5);
+    assert_eq!(distance(0, 0), -2.0f32.sqrt() + 0.5);
+
+    // Clamped to the spread, with the edge in the middle of the encoded range
+    assert_eq!(image.signed_distance_field(1.0).distances().get_pixel(2, 2).0[0], 1.0);
+    assert_eq!(field.to_luma8().get_pixel(1, 2).0[0], 159);
+    assert_eq!(field.to_luma8().get_pixel(0, 2).0[0], 96);
+}
@@ -0,0 +1,159 @@
+use std::path::{Path, PathBuf};
+use geo::{LineString, Polygon};
+use image::RgbaImage;
+use mimesis::mesh::{Mesh3D, MeshGroup, PolygonMesh};
+use serde_json::Value;
+
+/// Empty directory of the test, removed by the caller.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mimesis-{}-{}", std::process::id(), name));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn square() -> Mesh3D {
+    let polygon = Polygon::new(
+        LineString::from(vec![(0.0, 0.0), (8.0, 0.0), (8.0, 8.0), (0.0, 8.0)]),
+        vec![],
+    );
+    polygon.mesh2d().unwrap().extrude(3.0, 8.0, 8.0)
+}
+
+fn texture(dir: &Path) -> PathBuf {
+    let path = dir.join("texture.png");
+    RgbaImage::from_pixel(2, 2, [200, 100, 50, 255].into()).save(&path).unwrap();
+    path
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// JSON document and binary chunk of a GLB file, after checking its layout.
+fn read_glb(path: &Path) -> (Value, Vec<u8>) {
+    let bytes = std::fs::read(path).unwrap();
+    assert_eq!(&bytes[0..4], b"glTF");
+    assert_eq!(u32_at(&bytes, 4), 2);
+    assert_eq!(u32_at(&bytes, 8) as usize, bytes.len());
+
+    // Chunks follow the 12 byte header, their lengths keep them aligned on 4 bytes
+    let mut chunks = Vec::new();
+    let mut offset = 12;
+    while offset < bytes.len() {
+        let length = u32_at(&bytes, offset) as usize;
+        assert_eq!(length % 4, 0);
+        chunks.push((u32_at(&bytes, offset + 4), bytes[offset + 8..offset + 8 + length].to_vec()));
+        offset += 8 + length;
+    }
+    assert_eq!(offset, bytes.len());
+
+    assert_eq!(chunks[0].0, 0x4E4F_534A);
+    let json = serde_json::from_slice(&chunks[0].1).unwrap();
+    let buffer = match chunks.get(1) {
+        Some((kind, buffer)) => {
+            assert_eq!(*kind, 0x004E_4942);
+            buffer.clone()
+        }
+        None => Vec::new(),
+    };
+    (json, buffer)
+}
+
+/// Bytes of the buffer view of an accessor.
+fn accessor_bytes<'a>(json: &Value, buffer: &'a [u8], accessor: usize) -> &'a [u8] {
+    let view = &json["bufferViews"][json["accessors"][accessor]["bufferView"].as_u64().unwrap() as usize];
+    let offset = view["byteOffset"].as_u64().unwrap() as usize;
+    let length = view["byteLength"].as_u64().unwrap() as usize;
+    &buffer[offset..offset + length]
+}
+
+#[test]
+fn glb_round_trip() {
+    let dir = temp_dir("glb");
+    let texture = texture(&dir);
+    let path = dir.join("square.glb");
+    let mesh = square();
+    mesh.export_glb(&path, &texture, &texture, &texture).unwrap();
+    let (json, buffer) = read_glb(&path);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(json["buffers"][0]["byteLength"].as_u64().unwrap() as usize, buffer.len());
+    let primitives = json["meshes"][0]["primitives"].as_array().unwrap();
+    assert_eq!(primitives.len(), 3);
+    assert_eq!(json["materials"].as_array().unwrap().len(), 3);
+    assert_eq!(json["images"].as_array().unwrap().len(), 1);
+
+    // Positions and their bounds
+    let position = primitives[0]["attributes"]["POSITION"].as_u64().unwrap() as usize;
+    let accessor = &json["accessors"][position];
+    let count = accessor["count"].as_u64().unwrap() as usize;
+    assert_eq!(count, mesh.vertices.len());
+    let positions: Vec<f32> = accessor_bytes(&json, &buffer, position)
+        .chunks(4)
+        .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+        .collect();
+    assert_eq!(positions.len(), 3 * count);
+    for c in 0..3 {
+        let values = positions.iter().skip(c).step_by(3);
+        let min = values.clone().fold(f32::INFINITY, |a, &b| a.min(b));
+        let max = values.fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+        assert_eq!(accessor["min"][c].as_f64().unwrap(), min as f64);
+        assert_eq!(accessor["max"][c].as_f64().unwrap(), max as f64);
+    }
+    let bounds = |key: &str| -> Vec<f64> { (0..3).map(|c| accessor[key][c].as_f64().unwrap()).collect() };
+    assert_eq!(bounds("min"), [0.0, -8.0, 0.0]);
+    assert_eq!(bounds("max"), [8.0, 0.0, 3.0]);
+
+    // Every face group is a primitive with its own material and indices
+    for (i, (primitive, group)) in primitives.iter().zip(&mesh.faces).enumerate() {
+        assert_eq!(primitive["material"].as_u64().unwrap() as usize, i);
+        assert_eq!(json["materials"][i]["name"], group.name);
+        let indices = primitive["indices"].as_u64().unwrap() as usize;
+        assert_eq!(json["accessors"][indices]["count"].as_u64().unwrap() as usize, 3 * group.indices.len());
+        let values: Vec<u32> = accessor_bytes(&json, &buffer, indices)
+            .chunks(4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+        assert_eq!(values.len(), 3 * group.indices.len());
+        assert!(values.iter().all(|&index| (index as usize) < count));
+    }
+}
+
+#[test]
+fn mesh_without_faces_is_an_empty_scene() {
+    let dir = temp_dir("empty");
+    let texture = texture(&dir);
+    let path = dir.join("empty.glb");
+    let mesh = Mesh3D {
+        vertices: Vec::new(),
+        uvs: Vec::new(),
+        normals: Vec::new(),
+        faces: ["front", "back", "side"].map(|name| MeshGroup { indices: Vec::new(), name }).to_vec(),
+    };
+    mesh.export_glb(&path, &texture, &texture, &texture).unwrap();
+    let (json, buffer) = read_glb(&path);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(json["scenes"], serde_json::json!([{}]));
+    assert!(json.get("meshes").is_none());
+    assert!(json.get("accessors").is_none());
+
+    // The embedded texture still needs a buffer
+    assert!(!buffer.is_empty());
+    assert_eq!(json["buffers"][0]["byteLength"].as_u64().unwrap() as usize, buffer.len());
+}
+
+#[test]
+fn gltf_buffer_is_a_sibling_file() {
+    let dir = temp_dir("gltf");
+    let path = dir.join("square.gltf");
+    square().export_gltf(&path, "front.png", "back.png", "front.png").unwrap();
+
+    let json: Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    let bin_length = std::fs::metadata(dir.join("square.bin")).unwrap().len();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(json["buffers"][0]["uri"], "square.bin");
+    assert_eq!(json["buffers"][0]["byteLength"].as_u64().unwrap(), bin_length);
+    assert_eq!(json["images"], serde_json::json!([{ "uri": "textures/front.png" }, { "uri": "textures/back.png" }]));
+}
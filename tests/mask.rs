@@ -0,0 +1,228 @@
+use image::{DynamicImage, GrayImage, RgbImage, RgbaImage};
+use mimesis::{border_color, histogram, AdaptiveMethod, BinaryImage, ColorSpace, MaskChannel, ThresholdMethod};
+
+mod common;
+use common::Random;
+
+fn pixels(image: &BinaryImage) -> Vec<bool> {
+    (0..image.height())
+        .flat_map(|y| (0..image.width()).map(move |x| *image.get_pixel(x, y)))
+        .collect()
+}
+
+/// Histogram with a peak of 1000 pixels at `peak`, rising linearly from a flat tail of 10
+/// pixels at `knee`, and the tail running to `end`.
+fn knee_histogram(peak: usize, knee: usize, end: usize) -> [u64; 256] {
+    let mut histogram = [0u64; 256];
+    let (low, high) = (end.min(peak), end.max(peak));
+    for (value, count) in histogram.iter_mut().enumerate().take(high + 1).skip(low) {
+        let from_knee = value.abs_diff(knee) as f64;
+        let span = peak.abs_diff(knee) as f64;
+        let rising = (value >= knee) == (peak > knee);
+        *count = if rising { (10.0 + 990.0 * from_knee / span).round() as u64 } else { 10 };
+    }
+    histogram
+}
+
+#[test]
+fn otsu_splits_two_peaks() {
+    let mut histogram = [0u64; 256];
+    // Overlapping peaks of different sizes, whose valley is around 111
+    for (center, height) in [(50i64, 800.0), (200, 300.0)] {
+        for offset in -60i64..=60 {
+            let value = center + offset;
+            if (0..256).contains(&value) {
+                histogram[value as usize] += (height * (-(offset * offset) as f64 / 800.0).exp()).round() as u64;
+            }
+        }
+    }
+
+    let threshold = ThresholdMethod::Otsu.threshold(&histogram);
+    assert!(threshold > 90 && threshold < 140, "threshold {} is not in the valley between the peaks", threshold);
+}
+
+#[test]
+fn triangle_finds_the_knee_of_either_tail() {
+    // Tail on the dark side, the peak stays above the threshold
+    let dark = knee_histogram(200, 150, 50);
+    assert_eq!(ThresholdMethod::Triangle.threshold(&dark), 150);
+
+    // Tail on the bright side
+    let bright = knee_histogram(55, 105, 205);
+    assert_eq!(ThresholdMethod::Triangle.threshold(&bright), 105);
+}
+
+#[test]
+fn triangle_keeps_a_single_value_above_the_threshold() {
+    for value in [0, 100, 255] {
+        let mut histogram = [0u64; 256];
+        histogram[value] = 42;
+        assert_eq!(ThresholdMethod::Triangle.threshold(&histogram) as usize, value.min(254));
+    }
+
+    // Even a white image keeps its foreground
+    let image = DynamicImage::ImageLuma8(GrayImage::from_pixel(4, 3, [255].into()));
+    let (mask, threshold) = BinaryImage::from_channel(&image, MaskChannel::Luminance, ThresholdMethod::Triangle);
+    assert_eq!(threshold, 254);
+    assert!(pixels(&mask).iter().all(|&foreground| foreground));
+}
+
+#[test]
+fn percentile_reaches_its_bounds() {
+    let image = DynamicImage::ImageLuma8(GrayImage::from_fn(16, 16, |x, y| [(10 + (x + y) % 11) as u8].into()));
+    let gray = image.to_luma8();
+    let histogram = histogram(&gray);
+
+    assert_eq!(ThresholdMethod::Percentile(0.0).threshold(&histogram), 0);
+    assert_eq!(ThresholdMethod::Percentile(100.0).threshold(&histogram), 20);
+    assert_eq!(ThresholdMethod::Percentile(-5.0).threshold(&histogram), 0);
+    assert_eq!(ThresholdMethod::Percentile(150.0).threshold(&histogram), 20);
+
+    // Every pixel is above the 0th percentile and none above the 100th
+    let (mask, _) = BinaryImage::from_channel(&image, MaskChannel::Luminance, ThresholdMethod::Percentile(0.0));
+    assert!(pixels(&mask).iter().all(|&foreground| foreground));
+    let (mask, _) = BinaryImage::from_channel(&image, MaskChannel::Luminance, ThresholdMethod::Percentile(100.0));
+    assert!(pixels(&mask).iter().all(|&foreground| !foreground));
+
+    // The median of the values 0 to 255 taken once each
+    let uniform = [1u64; 256];
+    assert_eq!(ThresholdMethod::Percentile(50.0).threshold(&uniform), 127);
+}
+
+#[test]
+fn from_channel_returns_its_threshold() {
+    let gray = GrayImage::from_fn(32, 8, |x, y| [if (x / 4 + y) % 3 == 0 { 40 + (x % 7) as u8 } else { 180 + (y % 5) as u8 }].into());
+    let image = DynamicImage::ImageLuma8(gray.clone());
+
+    for method in [ThresholdMethod::Fixed(90), ThresholdMethod::Otsu, ThresholdMethod::Triangle, ThresholdMethod::Percentile(30.0)] {
+        let (mask, threshold) = BinaryImage::from_channel(&image, MaskChannel::Luminance, method);
+        assert_eq!(threshold, method.threshold(&histogram(&gray)), "{:?}", method);
+        let expected: Vec<bool> = gray.pixels().map(|pixel| pixel.0[0] > threshold).collect();
+        assert_eq!(pixels(&mask), expected, "{:?}", method);
+    }
+}
+
+#[test]
+fn color_distances_are_scaled_to_100() {
+    let (black, white) = ([0, 0, 0], [255, 255, 255]);
+    assert!((ColorSpace::Lab.distance(black, white) - 100.0).abs() < 1e-3);
+    assert!((ColorSpace::Hsv.distance(black, white) - 100.0).abs() < 1e-9);
+
+    // Saturated colors lie on the rim of the cone, a third of a turn apart
+    let (red, green) = ([255, 0, 0], [0, 255, 0]);
+    assert!((ColorSpace::Hsv.distance(red, black) - 100.0 * 2f64.sqrt()).abs() < 1e-9);
+    assert!((ColorSpace::Hsv.distance(red, green) - 100.0 * 3f64.sqrt()).abs() < 1e-9);
+    assert!((ColorSpace::Hsv.distance(red, white) - 100.0).abs() < 1e-9);
+
+    for space in [ColorSpace::Lab, ColorSpace::Hsv] {
+        assert_eq!(space.distance(red, red), 0.0);
+    }
+}
+
+#[test]
+fn border_color_ignores_a_covered_corner() {
+    let mut image = RgbImage::from_pixel(64, 48, [240, 235, 230].into());
+    for y in 0..20 {
+        for x in 0..24 {
+            image.put_pixel(x, y, [200, 30, 20].into());
+        }
+    }
+    assert_eq!(border_color(&DynamicImage::ImageRgb8(image)), [240, 235, 230]);
+}
+
+#[test]
+fn color_key_removes_a_green_screen() {
+    let key = [0, 177, 64];
+    let mut image = RgbaImage::from_fn(24, 16, |x, y| {
+        // Uneven lighting of the screen
+        let shade = ((x + y) % 5) as u8;
+        [shade, 177 - 2 * shade, 64 + shade, 255].into()
+    });
+    let mut expected = vec![false; 24 * 16];
+    for y in 4..12 {
+        for x in 6..18 {
+            let color = if x < 12 { [180, 120, 90, 255] } else { [30, 40, 160, 255] };
+            image.put_pixel(x, y, color.into());
+            expected[(y * 24 + x) as usize] = true;
+        }
+    }
+
+    // Transparent pixels are background whatever their color
+    image.put_pixel(8, 6, [180, 120, 90, 0].into());
+    expected[6 * 24 + 8] = false;
+
+    let image = DynamicImage::ImageRgba8(image);
+    for (space, tolerance) in [(ColorSpace::Lab, 15.0), (ColorSpace::Hsv, 15.0)] {
+        let mask = BinaryImage::from_color_key(&image, key, tolerance, space);
+        assert_eq!(pixels(&mask), expected, "{:?}", space);
+    }
+}
+
+/// Mean of the square of the given radius around every value, cropped to the image, by
+/// adding up the square.
+fn window_mean(values: &[f64], width: usize, height: usize, radius: usize) -> Vec<f64> {
+    let mut means = Vec::with_capacity(values.len());
+    for y in 0..height {
+        for x in 0..width {
+            let (mut sum, mut count) = (0.0, 0);
+            for wy in y.saturating_sub(radius)..(y + radius + 1).min(height) {
+                for wx in x.saturating_sub(radius)..(x + radius + 1).min(width) {
+                    sum += values[wy * width + wx];
+                    count += 1;
+                }
+            }
+            means.push(sum / count as f64);
+        }
+    }
+    means
+}
+
+#[test]
+fn adaptive_means_match_reference() {
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+    let offset = 3.0;
+
+    for (width, height) in [(1, 1), (5, 3), (17, 11), (40, 9)] {
+        let gray = GrayImage::from_fn(width, height, |_, _| [(random.next() % 256) as u8].into());
+        let image = DynamicImage::ImageLuma8(gray.clone());
+        let values: Vec<f64> = gray.iter().map(|&value| value as f64).collect();
+        let (w, h) = (width as usize, height as usize);
+
+        // Windows larger than the image, and Gaussian windows too small to be split in three
+        for window in [1, 3, 4, 7, 15, 51] {
+            let radius = (window / 2) as usize;
+            let gaussian_radius = (radius / 3).max(1);
+            let gaussian = window_mean(&window_mean(&window_mean(&values, w, h, gaussian_radius), w, h, gaussian_radius), w, h, gaussian_radius);
+            for (method, means) in [(AdaptiveMethod::Mean, window_mean(&values, w, h, radius)), (AdaptiveMethod::Gaussian, gaussian)] {
+                let mask = pixels(&BinaryImage::from_channel_adaptive(&image, MaskChannel::Luminance, method, window, offset));
+                for (i, (&value, mean)) in values.iter().zip(means).enumerate() {
+                    // Rounding of the summed-area tables may flip values right on the threshold
+                    if (value - (mean - offset)).abs() > 1e-6 {
+                        assert_eq!(mask[i], value > mean - offset, "{:?} window {} on {}x{} at pixel {}", method, window, width, height, i);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn adaptive_threshold_follows_uneven_lighting() {
+    // Light spots on a background lit from dark on the left to bright on the right
+    let (width, height) = (64, 48);
+    let spot = |x: u32, y: u32| x % 8 < 2 && y % 8 < 2;
+    let gray = GrayImage::from_fn(width, height, |x, y| {
+        let background = 40.0 + 120.0 * x as f64 / (width - 1) as f64;
+        [(background + if spot(x, y) { 50.0 } else { 0.0 }).round() as u8].into()
+    });
+    let image = DynamicImage::ImageLuma8(gray);
+    let expected: Vec<bool> = (0..height).flat_map(|y| (0..width).map(move |x| spot(x, y))).collect();
+
+    let (otsu, _) = BinaryImage::from_channel(&image, MaskChannel::Luminance, ThresholdMethod::Otsu);
+    assert_ne!(pixels(&otsu), expected);
+
+    for method in [AdaptiveMethod::Mean, AdaptiveMethod::Gaussian] {
+        let mask = BinaryImage::from_channel_adaptive(&image, MaskChannel::Luminance, method, 15, -15.0);
+        assert_eq!(pixels(&mask), expected, "{:?}", method);
+    }
+}
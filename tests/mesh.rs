@@ -0,0 +1,395 @@
+use std::collections::{HashMap, HashSet};
+use geo::{Area, LineString, Polygon};
+use image::GrayImage;
+use mimesis::mesh::{ExtrusionProfile, InflateProfile, Mesh2D, Mesh3D, PolygonMesh, Refinement, StlFormat, Triangulator};
+use mimesis::{Connectivity, TraceOptions};
+
+mod common;
+use common::mask;
+
+/// Square with a needle sticking out of its top right corner, whose two edges are almost
+/// parallel.
+fn spike() -> Polygon {
+    Polygon::new(
+        LineString::from(vec![(0.0, 0.0), (30.0, 0.0), (30.0, 30.0), (100.0, 31.0), (28.0, 30.0), (0.0, 30.0)]),
+        vec![LineString::from(vec![(10.0, 10.0), (10.0, 20.0), (20.0, 20.0), (20.0, 10.0)])],
+    )
+}
+
+/// Position of `point` along the segment from `a` to `b`, if it lies on it.
+fn along(a: [f64; 2], b: [f64; 2], point: [f64; 2]) -> Option<f64> {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let (px, py) = (point[0] - a[0], point[1] - a[1]);
+    let length_squared = dx * dx + dy * dy;
+    let on_segment = (px * dy - py * dx).abs() <= 1e-6 * length_squared;
+    on_segment.then_some((px * dx + py * dy) / length_squared)
+}
+
+#[test]
+fn refinement_splits_ring_edges_in_order() {
+    let polygon = spike();
+    let refinement = Refinement {
+        max_area: Some(4.0),
+        ..Default::default()
+    };
+    let mesh = polygon.mesh2d_with(&Triangulator::Delaunay(refinement)).unwrap();
+
+    let originals: Vec<Vec<[f64; 2]>> = std::iter::once(polygon.exterior()).chain(polygon.interiors())
+        .map(|ring| ring.coords().take(ring.coords().count() - 1).map(|coord| [coord.x, coord.y]).collect())
+        .collect();
+    assert_eq!(mesh.rings.len(), originals.len());
+
+    let mut split = false;
+    for (range, original) in mesh.rings.iter().zip(&originals) {
+        let ring = &mesh.vertices[range.clone()];
+        split |= ring.len() > original.len();
+        assert_eq!(ring[0], original[0]);
+
+        // Every vertex is the next original vertex, or lies further along the current edge
+        let mut edge = 0;
+        let mut t = 0.0;
+        for &vertex in &ring[1..] {
+            let next = original[(edge + 1) % original.len()];
+            if vertex == next {
+                edge += 1;
+                t = 0.0;
+                continue;
+            }
+            let position = along(original[edge], next, vertex)
+                .unwrap_or_else(|| panic!("{:?} is not on edge {} of the ring", vertex, edge));
+            assert!(position > t && position < 1.0, "{:?} is out of order on edge {}", vertex, edge);
+            t = position;
+        }
+        assert_eq!(edge, original.len() - 1);
+    }
+    assert!(split, "refinement did not split any ring edge");
+
+    let area: f64 = mesh.indices.chunks(3)
+        .map(|face| {
+            let [a, b, c] = [mesh.vertices[face[0]], mesh.vertices[face[1]], mesh.vertices[face[2]]];
+            ((b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1])).abs() / 2.0
+        })
+        .sum();
+    assert!((area - polygon.unsigned_area()).abs() < 1e-6);
+}
+
+fn extruded_frame() -> Mesh3D {
+    let polygon = Polygon::new(
+        LineString::from(vec![(0.0, 0.0), (40.0, 0.0), (40.0, 30.0), (0.0, 30.0)]),
+        vec![LineString::from(vec![(10.0, 10.0), (10.0, 20.0), (30.0, 20.0), (30.0, 10.0)])],
+    );
+    polygon.mesh2d().unwrap().extrude(5.0, 40.0, 30.0)
+}
+
+#[test]
+fn extruded_polygon_with_hole_is_watertight() {
+    let mesh = extruded_frame();
+    mesh.check_watertight().unwrap();
+
+    let path = std::env::temp_dir().join(format!("mimesis-{}.stl", std::process::id()));
+    mesh.export_stl(&path, StlFormat::Binary, 0.1).unwrap();
+    let length = std::fs::metadata(&path).unwrap().len() as usize;
+    std::fs::remove_file(&path).unwrap();
+
+    let triangles: usize = mesh.faces.iter().map(|group| group.indices.len()).sum();
+    assert_eq!(length, 84 + 50 * triangles);
+}
+
+#[test]
+fn contour_touching_itself_is_watertight() {
+    // Two pixels meet only diagonally, the walls share the vertical edge at that vertex
+    let image = mask(&[
+        "###.",
+        "##.#",
+        "####",
+    ]);
+    for connectivity in [Connectivity::Four, Connectivity::Eight] {
+        let polygons = image.trace_polygons_with(&TraceOptions {
+            connectivity,
+            ..Default::default()
+        });
+        assert_eq!(polygons.len(), 1);
+
+        let mesh = polygons[0].mesh2d().unwrap().extrude(2.0, 4.0, 3.0);
+        let path = std::env::temp_dir().join(format!("mimesis-{}-{:?}.stl", std::process::id(), connectivity));
+        mesh.export_stl(&path, StlFormat::Binary, 1.0).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn missing_or_flipped_face_is_not_watertight() {
+    let mut missing = extruded_frame();
+    missing.faces[2].indices.pop();
+    assert!(missing.check_watertight().is_err());
+
+    let mut flipped = extruded_frame();
+    flipped.faces[0].indices[0].swap(1, 2);
+    assert!(flipped.check_watertight().is_err());
+
+    let path = std::env::temp_dir().join(format!("mimesis-{}-flipped.stl", std::process::id()));
+    assert!(flipped.export_stl(&path, StlFormat::Binary, 1.0).is_err());
+    assert!(!path.exists());
+}
+
+/// Block with a one pixel wide arm, much thinner than the bevels below.
+fn thin_arm() -> Polygon {
+    Polygon::new(
+        LineString::from(vec![(0.0, 0.0), (40.0, 0.0), (40.0, 14.0), (70.0, 14.0), (70.0, 15.0), (40.0, 15.0), (40.0, 30.0), (0.0, 30.0)]),
+        vec![],
+    )
+}
+
+#[test]
+fn bevel_is_narrowed_on_thin_features() {
+    let polygon = thin_arm();
+    for triangulator in [Triangulator::Earcut, Triangulator::Delaunay(Refinement::default())] {
+        let mesh = polygon.mesh2d_with(&triangulator).unwrap()
+            .extrude_profile(10.0, 70.0, 30.0, ExtrusionProfile::Chamfer { width: 4.0 }, &triangulator).unwrap()
+            .expect("bevel fell back to flat edges");
+        mesh.check_watertight().unwrap();
+
+        // The block keeps the full bevel, the arm a narrower one within its own width
+        let front = &mesh.faces[0].indices;
+        let front_vertices: Vec<[f64; 3]> = front.iter().flatten().map(|&i| mesh.vertices[i]).collect();
+        assert!(front_vertices.iter().all(|&[_, _, z]| z == 10.0));
+        assert!(front_vertices.iter().any(|&[x, y, _]| (x - 4.0).abs() < 1e-9 && (y + 4.0).abs() < 1e-9));
+        for &[x, y, _] in front_vertices.iter().filter(|&&[x, _, _]| x > 41.0) {
+            assert!(x < 70.0 && y < -14.0 && y > -15.0, "({}, {}) is outside of the arm", x, y);
+        }
+    }
+}
+
+#[test]
+fn bevel_does_not_merge_inset_points() {
+    // Steps of one pixel, two adjacent corners collapse to the same point when inset by 1.5
+    let polygon = Polygon::new(
+        LineString::from(vec![(0.0, 0.0), (8.0, 0.0), (8.0, 1.0), (9.0, 1.0), (9.0, 2.0), (12.0, 2.0), (12.0, 3.0), (13.0, 3.0), (13.0, 12.0), (0.0, 12.0)]),
+        vec![],
+    );
+    let triangulator = Triangulator::Delaunay(Refinement::default());
+    let mesh = polygon.mesh2d_with(&triangulator).unwrap()
+        .extrude_profile(5.0, 13.0, 12.0, ExtrusionProfile::Round { width: 1.5, segments: 4 }, &triangulator).unwrap()
+        .expect("bevel fell back to flat edges");
+    mesh.check_watertight().unwrap();
+}
+
+fn extruded_square() -> Mesh3D {
+    let polygon = Polygon::new(
+        LineString::from(vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]),
+        vec![],
+    );
+    polygon.mesh2d().unwrap().extrude(4.0, 10.0, 10.0)
+}
+
+#[test]
+fn crease_splits_square_corners() {
+    let mut mesh = extruded_square();
+    let vertex_count = mesh.vertices.len();
+    mesh.compute_normals(30f64.to_radians());
+    assert!(mesh.has_normals());
+    assert!(mesh.vertices.len() > vertex_count);
+    assert_eq!(mesh.uvs.len(), mesh.vertices.len());
+
+    for (group, normal) in mesh.faces[..2].iter().zip([[0.0, 0.0, 1.0], [0.0, 0.0, -1.0]]) {
+        assert!(group.indices.iter().flatten().all(|&i| mesh.normals[i] == normal), "{} normals", group.name);
+    }
+
+    // Every side corner takes the axis aligned normal of its own wall
+    let axes = [[1.0, 0.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, -1.0, 0.0]];
+    let mut normals_at: HashMap<[u64; 3], HashSet<[u64; 3]>> = HashMap::new();
+    for &[i0, i1, i2] in &mesh.faces[2].indices {
+        let normal = mesh.normals[i0];
+        assert!(axes.contains(&normal), "{:?} is not axis aligned", normal);
+        assert_eq!(mesh.normals[i1], normal);
+        assert_eq!(mesh.normals[i2], normal);
+        for i in [i0, i1, i2] {
+            normals_at.entry(mesh.vertices[i].map(f64::to_bits)).or_default().insert(normal.map(f64::to_bits));
+        }
+    }
+
+    // The four vertical edges are split at both ends
+    assert_eq!(normals_at.values().filter(|normals| normals.len() == 2).count(), 8);
+    assert!(normals_at.values().all(|normals| normals.len() <= 2));
+}
+
+#[test]
+fn smooth_sides_average_circle_normals() {
+    let (center, radius) = (20.0, 15.0);
+    let points: Vec<(f64, f64)> = (0..64)
+        .map(|k| {
+            let angle = k as f64 / 64.0 * std::f64::consts::TAU;
+            (center + radius * angle.cos(), center + radius * angle.sin())
+        })
+        .collect();
+    let mut mesh = Polygon::new(LineString::from(points), vec![])
+        .mesh2d().unwrap()
+        .extrude(5.0, 40.0, 40.0);
+    mesh.compute_normals(30f64.to_radians());
+
+    // Mesh coordinates flip the y axis of the image
+    for &i in mesh.faces[2].indices.iter().flatten() {
+        let [x, y, _] = mesh.vertices[i];
+        let radial = [(x - center) / radius, (y + center) / radius];
+        let normal = mesh.normals[i];
+        assert!(normal[2].abs() < 1e-9);
+        assert!(normal[0] * radial[0] + normal[1] * radial[1] > 0.9999, "{:?} is not radial at ({}, {})", normal, x, y);
+    }
+}
+
+/// Vertex references of the faces of an OBJ file, split on slashes.
+fn obj_faces(path: &std::path::Path) -> (Vec<Vec<Vec<String>>>, HashMap<String, usize>) {
+    let content = std::fs::read_to_string(path).unwrap();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut faces = Vec::new();
+    for line in content.lines() {
+        let mut tokens = line.split_whitespace();
+        let Some(kind) = tokens.next() else {
+            continue;
+        };
+        *counts.entry(kind.to_string()).or_default() += 1;
+        if kind == "f" {
+            faces.push(tokens.map(|token| token.split('/').map(str::to_string).collect()).collect());
+        }
+    }
+    (faces, counts)
+}
+
+#[test]
+fn obj_faces_reference_normals() {
+    let dir = std::env::temp_dir().join(format!("mimesis-{}-obj", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (obj, mtl) = (dir.join("square.obj"), dir.join("square.mtl"));
+
+    let mut mesh = extruded_square();
+    let triangles: usize = mesh.faces.iter().map(|group| group.indices.len()).sum();
+    mesh.export_obj(&obj, &mtl, "front.png", "back.png", "side.png").unwrap();
+    let (flat_faces, flat_counts) = obj_faces(&obj);
+
+    mesh.compute_normals(30f64.to_radians());
+    mesh.export_obj(&obj, &mtl, "front.png", "back.png", "side.png").unwrap();
+    let (faces, counts) = obj_faces(&obj);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    // Without normals the faces are v/vt pairs
+    assert_eq!(flat_faces.len(), triangles);
+    assert!(!flat_counts.contains_key("vn"));
+    assert!(flat_faces.iter().flatten().all(|corner| corner.len() == 2 && corner[0] == corner[1]));
+
+    // With normals they are v/vt/vn triplets of the same 1-based index
+    assert_eq!(faces.len(), triangles);
+    for kind in ["v", "vt", "vn"] {
+        assert_eq!(counts[kind], mesh.vertices.len(), "{} lines", kind);
+    }
+    let expected: Vec<Vec<Vec<String>>> = mesh.faces.iter()
+        .flat_map(|group| group.indices.iter())
+        .map(|triangle| triangle.iter().map(|i| vec![(i + 1).to_string(); 3]).collect())
+        .collect();
+    assert_eq!(faces, expected);
+}
+
+#[test]
+fn inflation_is_symmetric_and_reaches_its_height() {
+    // Squares of different sizes each reach the full height at their center
+    let image = mask(&[
+        "..........................",
+        ".################.........",
+        ".################.........",
+        ".################.........",
+        ".################.........",
+        ".################..######.",
+        ".################..######.",
+        ".################..######.",
+        ".################..######.",
+        ".################..######.",
+        ".################..######.",
+        ".################.........",
+        ".################.........",
+        ".################.........",
+        ".################.........",
+        ".################.........",
+        ".################.........",
+        "..........................",
+    ]);
+    let polygons = image.trace_polygons(0);
+    assert_eq!(polygons.len(), 2);
+    let distances = image.distance_transform();
+    let (depth, height) = (4.0, 6.0);
+
+    for polygon in &polygons {
+        let mesh2d = polygon.mesh2d_with_interior(1.0, None).unwrap();
+        let n = mesh2d.vertices.len();
+        let contour_len = mesh2d.rings.iter().map(|ring| ring.end).max().unwrap();
+        assert!(n > contour_len);
+
+        for profile in [InflateProfile::Circular, InflateProfile::Parabolic, InflateProfile::Linear] {
+            let mesh = mesh2d.inflate(depth, 26.0, 18.0, &distances, height, profile);
+
+            // Back vertices come first, then the front vertices in the same order
+            for i in 0..contour_len {
+                assert_eq!(mesh.vertices[i][2], 0.0);
+                assert_eq!(mesh.vertices[i + n][2], depth);
+            }
+            for i in contour_len..n {
+                let (back, front) = (-mesh.vertices[i][2], mesh.vertices[i + n][2] - depth);
+                assert!((back - front).abs() < 1e-12, "{:?} vertex {} is not symmetric", profile, i);
+                assert!((0.0..=height).contains(&front));
+            }
+
+            let top = mesh.vertices[n..2 * n].iter().map(|vertex| vertex[2]).fold(f64::MIN, f64::max);
+            let bottom = mesh.vertices[..n].iter().map(|vertex| vertex[2]).fold(f64::MAX, f64::min);
+            assert!((top - depth - height).abs() < 1e-12, "{:?} reaches {}", profile, top);
+            assert!((bottom + height).abs() < 1e-12, "{:?} reaches {}", profile, bottom);
+        }
+    }
+}
+
+fn relief_plate() -> Mesh2D {
+    let polygon = Polygon::new(
+        LineString::from(vec![(0.0, 0.0), (20.0, 0.0), (20.0, 10.0), (0.0, 10.0)]),
+        vec![],
+    );
+    polygon.mesh2d_with_interior(2.0, None).unwrap()
+}
+
+#[test]
+fn white_relief_raises_the_front_interior() {
+    let mesh2d = relief_plate();
+    let (depth, scale) = (3.0, 5.0);
+    let flat = mesh2d.extrude(depth, 20.0, 10.0);
+    let mesh = mesh2d.relief(depth, 20.0, 10.0, &GrayImage::from_pixel(20, 10, [255].into()), scale);
+    let n = mesh2d.vertices.len();
+    let contour_len = mesh2d.rings.iter().map(|ring| ring.end).max().unwrap();
+    assert!(n > contour_len);
+
+    // Only the interior of the front face moves, the side walls are left as they are
+    assert_eq!(mesh.vertices.len(), flat.vertices.len());
+    for (i, (vertex, flat)) in mesh.vertices.iter().zip(&flat.vertices).enumerate() {
+        let raised = (n + contour_len..2 * n).contains(&i);
+        let expected = if raised { [flat[0], flat[1], depth + scale] } else { *flat };
+        assert_eq!(*vertex, expected, "vertex {}", i);
+    }
+    assert_eq!(mesh.uvs, flat.uvs);
+}
+
+#[test]
+fn relief_depth_map_is_stretched_over_the_image() {
+    // A two pixel wide map covers the 20 pixel wide image, white on the left half
+    let depth_map = GrayImage::from_fn(2, 1, |x, _| [if x == 0 { 255 } else { 0 }].into());
+    let mesh2d = relief_plate();
+    let (depth, scale) = (3.0, 5.0);
+    let mesh = mesh2d.relief(depth, 20.0, 10.0, &depth_map, scale);
+    let n = mesh2d.vertices.len();
+    let contour_len = mesh2d.rings.iter().map(|ring| ring.end).max().unwrap();
+
+    // Stretched pixel centers lie at x = 5 and x = 15, the map is interpolated in between
+    let mut sides = (false, false);
+    for i in contour_len..n {
+        let x = mesh2d.vertices[i][0];
+        let expected = depth + scale * (1.5 - x / 10.0).clamp(0.0, 1.0);
+        assert!((mesh.vertices[i + n][2] - expected).abs() < 1e-9, "vertex at x = {} is at {}", x, mesh.vertices[i + n][2]);
+        sides.0 |= x < 5.0;
+        sides.1 |= x > 15.0;
+    }
+    assert_eq!(sides, (true, true));
+}
@@ -0,0 +1,67 @@
+use mimesis::{BinaryImage, StructuringElement};
+
+mod common;
+use common::Random;
+
+fn contains(element: StructuringElement, dx: i64, dy: i64) -> bool {
+    match element {
+        StructuringElement::Square(radius) => dx.abs() <= radius as i64 && dy.abs() <= radius as i64,
+        StructuringElement::Cross(radius) => (dx == 0 || dy == 0) && dx.abs().max(dy.abs()) <= radius as i64,
+        StructuringElement::Disk(radius) => dx * dx + dy * dy <= (radius * radius) as i64,
+    }
+}
+
+/// Pixel by pixel erosion or dilation, pixels outside of the image being background.
+fn reference(image: &BinaryImage, element: StructuringElement, erode: bool) -> Vec<bool> {
+    let (width, height) = (image.width() as i64, image.height() as i64);
+    let radius = match element {
+        StructuringElement::Square(radius) | StructuringElement::Cross(radius) | StructuringElement::Disk(radius) => radius as i64,
+    };
+    let pixel = |x: i64, y: i64| (0..width).contains(&x) && (0..height).contains(&y) && *image.get_pixel(x as u32, y as u32);
+
+    let mut result = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let mut neighbours = (-radius..=radius)
+                .flat_map(|dy| (-radius..=radius).map(move |dx| (dx, dy)))
+                .filter(|&(dx, dy)| contains(element, dx, dy))
+                .map(|(dx, dy)| pixel(x + dx, y + dy));
+            result.push(if erode { neighbours.all(|set| set) } else { neighbours.any(|set| set) });
+        }
+    }
+    result
+}
+
+fn pixels(image: &BinaryImage) -> Vec<bool> {
+    (0..image.height())
+        .flat_map(|y| (0..image.width()).map(move |x| *image.get_pixel(x, y)))
+        .collect()
+}
+
+#[test]
+fn erode_and_dilate_match_reference() {
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+
+    // Widths on both sides of the 64 bit word boundaries
+    for (width, height) in [(1, 1), (7, 5), (63, 9), (64, 12), (65, 8), (127, 6), (129, 11)] {
+        for density in [20, 50, 85] {
+            let image = random.mask(width, height, density);
+            for radius in 0..5 {
+                for element in [StructuringElement::Square(radius), StructuringElement::Cross(radius), StructuringElement::Disk(radius)] {
+                    assert_eq!(pixels(&image.erode(element)), reference(&image, element, true), "erode {:?} on {}x{}", element, width, height);
+                    assert_eq!(pixels(&image.dilate(element)), reference(&image, element, false), "dilate {:?} on {}x{}", element, width, height);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn open_and_close_compose_erode_and_dilate() {
+    let mut random = Random(0x9e37_79b9_7f4a_7c15);
+    let image = random.mask(70, 20, 50);
+    for element in [StructuringElement::Square(2), StructuringElement::Cross(3), StructuringElement::Disk(3)] {
+        assert_eq!(pixels(&image.open(element)), pixels(&image.erode(element).dilate(element)));
+        assert_eq!(pixels(&image.close(element)), pixels(&image.dilate(element).erode(element)));
+    }
+}
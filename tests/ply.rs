@@ -0,0 +1,129 @@
+use std::path::PathBuf;
+use geo::{LineString, Polygon};
+use image::RgbaImage;
+use mimesis::mesh::{Mesh3D, PlyFormat, PolygonMesh};
+
+/// Empty directory of the test, removed by the caller.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mimesis-{}-{}", std::process::id(), name));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn square() -> Mesh3D {
+    let polygon = Polygon::new(
+        LineString::from(vec![(0.0, 0.0), (8.0, 0.0), (8.0, 8.0), (0.0, 8.0)]),
+        vec![],
+    );
+    polygon.mesh2d().unwrap().extrude(3.0, 8.0, 8.0)
+}
+
+/// Transparent texture with an opaque pixel of a different color in each corner.
+fn texture() -> RgbaImage {
+    let mut texture = RgbaImage::new(8, 8);
+    texture.put_pixel(0, 0, [255, 0, 0, 255].into());
+    texture.put_pixel(7, 0, [0, 255, 0, 255].into());
+    texture.put_pixel(0, 7, [0, 0, 255, 255].into());
+    texture.put_pixel(7, 7, [255, 255, 0, 128].into());
+    texture
+}
+
+/// Header lines and body of a PLY file.
+fn split_header(bytes: &[u8]) -> (Vec<String>, &[u8]) {
+    let end = b"end_header\n";
+    let position = bytes.windows(end.len()).position(|window| window == end).unwrap() + end.len();
+    let header = String::from_utf8(bytes[..position].to_vec()).unwrap();
+    (header.lines().map(str::to_string).collect(), &bytes[position..])
+}
+
+fn element_count(header: &[String], element: &str) -> usize {
+    let prefix = format!("element {} ", element);
+    header.iter().find_map(|line| line.strip_prefix(&prefix)).unwrap().parse().unwrap()
+}
+
+#[test]
+fn ply_ascii_and_binary_agree() {
+    let dir = temp_dir("ply");
+    let (ascii_path, binary_path) = (dir.join("ascii.ply"), dir.join("binary.ply"));
+    let mesh = square();
+    let texture = texture();
+    mesh.export_ply(&ascii_path, PlyFormat::Ascii, Some(&texture)).unwrap();
+    mesh.export_ply(&binary_path, PlyFormat::BinaryLittleEndian, Some(&texture)).unwrap();
+    let ascii = std::fs::read(&ascii_path).unwrap();
+    let binary = std::fs::read(&binary_path).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let faces: Vec<([usize; 3], usize, &str)> = mesh.faces.iter().enumerate()
+        .flat_map(|(g, group)| group.indices.iter().map(move |&triangle| (triangle, g, group.name)))
+        .collect();
+
+    // Every vertex lies on a corner of the square and takes the color of the corner pixel
+    let colors: Vec<[u8; 4]> = mesh.vertices.iter()
+        .map(|&[x, y, _]| texture.get_pixel(x.min(7.0) as u32, (-y).min(7.0) as u32).0)
+        .collect();
+    assert!(colors.iter().all(|color| color[3] > 0));
+
+    let (header, body) = split_header(&ascii);
+    assert_eq!(header[1], "format ascii 1.0");
+    assert_eq!(element_count(&header, "vertex"), mesh.vertices.len());
+    assert_eq!(element_count(&header, "face"), faces.len());
+    let lines: Vec<&str> = std::str::from_utf8(body).unwrap().lines().collect();
+    assert_eq!(lines.len(), mesh.vertices.len() + faces.len());
+    for (i, line) in lines[..mesh.vertices.len()].iter().enumerate() {
+        let values: Vec<&str> = line.split(' ').collect();
+        let position: Vec<f64> = values[..3].iter().map(|value| value.parse().unwrap()).collect();
+        let color: Vec<u8> = values[5..].iter().map(|value| value.parse().unwrap()).collect();
+        assert_eq!(position, mesh.vertices[i]);
+        assert_eq!(color, colors[i]);
+    }
+    for (line, (triangle, group, name)) in lines[mesh.vertices.len()..].iter().zip(&faces) {
+        let mut expected = vec![3, triangle[0], triangle[1], triangle[2], *group, name.len()];
+        expected.extend(name.bytes().map(usize::from));
+        let values: Vec<usize> = line.split(' ').map(|value| value.parse().unwrap()).collect();
+        assert_eq!(values, expected);
+    }
+
+    // Binary vertices are 5 floats and 4 color bytes, faces a count, 3 ints, the group and its name
+    let (header, body) = split_header(&binary);
+    assert_eq!(header[1], "format binary_little_endian 1.0");
+    assert_eq!(element_count(&header, "vertex"), mesh.vertices.len());
+    assert_eq!(element_count(&header, "face"), faces.len());
+    let face_bytes: usize = faces.iter().map(|(_, _, name)| 15 + name.len()).sum();
+    assert_eq!(body.len(), mesh.vertices.len() * 24 + face_bytes);
+
+    for (i, vertex) in body.chunks(24).take(mesh.vertices.len()).enumerate() {
+        let position: Vec<f64> = vertex[..12].chunks(4)
+            .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()) as f64)
+            .collect();
+        assert_eq!(position, mesh.vertices[i]);
+        assert_eq!(vertex[20..], colors[i]);
+    }
+    let mut offset = mesh.vertices.len() * 24;
+    for (triangle, group, name) in &faces {
+        let face = &body[offset..offset + 15 + name.len()];
+        let indices: Vec<usize> = face[1..13].chunks(4)
+            .map(|bytes| i32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+            .collect();
+        assert_eq!(face[0], 3);
+        assert_eq!(indices, triangle);
+        assert_eq!(usize::from(face[13]), *group);
+        assert_eq!(&face[15..], name.as_bytes());
+        assert_eq!(usize::from(face[14]), name.len());
+        offset += face.len();
+    }
+}
+
+#[test]
+fn ply_without_texture_has_no_colors() {
+    let dir = temp_dir("ply-plain");
+    let path = dir.join("plain.ply");
+    let mesh = square();
+    mesh.export_ply(&path, PlyFormat::BinaryLittleEndian, None).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let (header, body) = split_header(&bytes);
+    assert!(!header.iter().any(|line| line.ends_with(" red")));
+    let faces: usize = mesh.faces.iter().map(|group| group.indices.len() * (15 + group.name.len())).sum();
+    assert_eq!(body.len(), mesh.vertices.len() * 20 + faces);
+}
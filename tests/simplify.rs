@@ -0,0 +1,165 @@
+use geo::{LineString, Polygon, Simplify, Validation};
+use mimesis::{simplify_polygon, simplify_polygons, SimplifyTarget};
+
+mod common;
+use common::Random;
+
+fn ring(points: &[(f64, f64)]) -> LineString {
+    LineString::from(points.to_vec())
+}
+
+fn square(x: f64, y: f64, side: f64) -> LineString {
+    ring(&[(x, y), (x + side, y), (x + side, y + side), (x, y + side)])
+}
+
+/// Star-shaped ring of `count` vertices at random distances between `radius` and twice it.
+fn wobbly(random: &mut Random, (x, y): (f64, f64), radius: f64, count: usize) -> LineString {
+    let points: Vec<(f64, f64)> = (0..count)
+        .map(|k| {
+            let angle = k as f64 / count as f64 * std::f64::consts::TAU;
+            let distance = radius * (1.0 + (random.next() % 1000) as f64 / 1000.0);
+            (x + distance * angle.cos(), y + distance * angle.sin())
+        })
+        .collect();
+    LineString::from(points)
+}
+
+fn vertex_count(ring: &LineString) -> usize {
+    ring.0.len() - 1
+}
+
+#[test]
+fn hole_stays_inside_simplified_exterior() {
+    // The shallow bulge of the top edge is within the tolerance, the hole reaches into it
+    let polygon = Polygon::new(
+        ring(&[(0.0, 0.0), (100.0, 0.0), (100.0, 50.0), (50.0, 60.0), (0.0, 50.0)]),
+        vec![ring(&[(35.0, 35.0), (65.0, 35.0), (65.0, 55.0), (35.0, 55.0)])],
+    );
+    assert!(polygon.is_valid());
+    assert!(!polygon.simplify(&12.0).is_valid(), "plain Ramer-Douglas-Peucker should flatten the bulge over the hole");
+
+    let simplified = simplify_polygon(&polygon, 12.0);
+    let result = simplified.polygon.expect("exterior should not degenerate");
+    assert!(result.is_valid());
+    assert_eq!(result.interiors().len(), 1);
+    assert!(simplified.dropped_holes.is_empty());
+    assert!(result.exterior().coords().any(|coord| coord.x == 50.0 && coord.y == 60.0));
+}
+
+#[test]
+fn collapsed_hole_is_reported() {
+    let polygon = Polygon::new(
+        square(0.0, 0.0, 100.0),
+        vec![square(10.0, 10.0, 3.0), square(40.0, 40.0, 30.0)],
+    );
+
+    let simplified = simplify_polygon(&polygon, 5.0);
+    let result = simplified.polygon.expect("exterior should not degenerate");
+    assert!(result.is_valid());
+    assert_eq!(simplified.dropped_holes, vec![0]);
+    assert_eq!(result.interiors(), &polygon.interiors()[1..]);
+}
+
+#[test]
+fn degenerate_exterior_drops_polygon() {
+    let polygon = Polygon::new(square(0.0, 0.0, 3.0), vec![square(1.0, 1.0, 1.0)]);
+
+    let simplified = simplify_polygon(&polygon, 5.0);
+    assert!(simplified.polygon.is_none());
+
+    // The holes of a dropped polygon are not reported on their own
+    assert!(simplified.dropped_holes.is_empty());
+}
+
+#[test]
+fn budget_takes_smallest_tolerance_within_budget() {
+    let mut random = Random(0x5EED);
+    let polygon = Polygon::new(wobbly(&mut random, (0.0, 0.0), 50.0, 200), vec![]);
+
+    for budget in [3, 4, 10, 25, 60, 199, 200, 500] {
+        let simplified = simplify_polygons(std::slice::from_ref(&polygon), SimplifyTarget::PolygonBudget(budget)).remove(0);
+        let exterior = simplified.polygon.expect("exterior should not degenerate").exterior().clone();
+        let tolerance = simplified.tolerances[0];
+        assert!(vertex_count(&exterior) <= budget, "{} vertices for a budget of {}", vertex_count(&exterior), budget);
+
+        // A ring within the budget is left whole, otherwise any smaller tolerance exceeds it
+        if budget >= 200 {
+            assert_eq!(tolerance, 0.0);
+            assert_eq!(exterior, *polygon.exterior());
+        } else {
+            assert!(tolerance > 0.0);
+            assert_eq!(exterior, polygon.exterior().simplify(&tolerance));
+            let below = polygon.exterior().simplify(&(tolerance * (1.0 - 1e-9)));
+            assert!(vertex_count(&below) > budget, "{} vertices below the tolerance for a budget of {}", vertex_count(&below), budget);
+        }
+    }
+}
+
+#[test]
+fn total_budget_is_shared_by_perimeter() {
+    let mut random = Random(0xB0D6E7);
+    let small = Polygon::new(wobbly(&mut random, (0.0, 0.0), 20.0, 150), vec![]);
+    let large = Polygon::new(
+        wobbly(&mut random, (200.0, 0.0), 60.0, 150),
+        vec![wobbly(&mut random, (200.0, 0.0), 10.0, 150)],
+    );
+    let tiny = Polygon::new(wobbly(&mut random, (0.0, 200.0), 1.0, 150), vec![]);
+    let polygons = [small, large, tiny];
+
+    let budget = 120;
+    let perimeters: Vec<f64> = polygons.iter()
+        .flat_map(|polygon| std::iter::once(polygon.exterior()).chain(polygon.interiors()))
+        .map(|ring| ring.lines().map(|line| line.dx().hypot(line.dy())).sum())
+        .collect();
+    let total: f64 = perimeters.iter().sum();
+    let shares: Vec<usize> = perimeters.iter()
+        .map(|perimeter| (budget as f64 * perimeter / total).floor() as usize)
+        .collect();
+    assert!(shares[3] < 3, "the tiny ring should get less than three vertices");
+
+    let simplified = simplify_polygons(&polygons, SimplifyTarget::TotalBudget(budget));
+    let originals: Vec<&LineString> = polygons[..2].iter()
+        .flat_map(|polygon| std::iter::once(polygon.exterior()).chain(polygon.interiors()))
+        .collect();
+    let tolerances: Vec<f64> = simplified[..2].iter().flat_map(|simplified| simplified.tolerances.clone()).collect();
+    let rings: Vec<LineString> = simplified[..2].iter()
+        .flat_map(|simplified| {
+            let polygon = simplified.polygon.clone().expect("exterior should not degenerate");
+            std::iter::once(polygon.exterior().clone()).chain(polygon.interiors().iter().cloned()).collect::<Vec<_>>()
+        })
+        .collect();
+    assert_eq!(rings.len(), 3);
+    for (((ring, original), tolerance), share) in rings.iter().zip(originals).zip(tolerances).zip(&shares) {
+        assert!(vertex_count(ring) <= *share, "{} vertices for a share of {}", vertex_count(ring), share);
+        let below = original.simplify(&(tolerance * (1.0 - 1e-9)));
+        assert!(vertex_count(&below) > *share, "{} vertices below the tolerance for a share of {}", vertex_count(&below), share);
+    }
+    assert!(rings.iter().map(vertex_count).sum::<usize>() <= budget);
+
+    // A share below three vertices leaves the ring degenerate
+    assert!(simplified[2].polygon.is_none());
+}
+
+#[test]
+fn relative_tolerance_scales_with_the_polygon() {
+    let mut random = Random(0x5CA1E);
+    let polygon = Polygon::new(wobbly(&mut random, (0.0, 0.0), 10.0, 100), vec![]);
+    let scaled = Polygon::new(
+        polygon.exterior().coords().map(|coord| (coord.x * 8.0, coord.y * 8.0)).collect(),
+        vec![],
+    );
+    let fraction = 0.01;
+
+    let simplified = simplify_polygons(&[polygon.clone(), scaled.clone()], SimplifyTarget::RelativeTolerance(fraction));
+    for (original, simplified) in [&polygon, &scaled].into_iter().zip(&simplified) {
+        let rect = geo::BoundingRect::bounding_rect(original).unwrap();
+        let tolerance = fraction * rect.width().hypot(rect.height());
+        assert!((simplified.tolerances[0] - tolerance).abs() < 1e-9);
+        assert_eq!(*simplified.polygon.as_ref().unwrap().exterior(), original.exterior().simplify(&simplified.tolerances[0]));
+    }
+
+    // The scaled polygon keeps the same vertices
+    let kept = |simplified: &mimesis::Simplified| vertex_count(simplified.polygon.as_ref().unwrap().exterior());
+    assert_eq!(kept(&simplified[0]), kept(&simplified[1]));
+    assert!(kept(&simplified[0]) < 100);
+}
@@ -0,0 +1,44 @@
+use geo::{Coord, LineString, Polygon};
+use mimesis::{smooth_polygon, SmoothCurve, SmoothOptions};
+
+/// Sharp tip at the origin closed by an arc of small turns.
+fn teardrop() -> Polygon {
+    let mut points = vec![(0.0, 0.0)];
+    for k in 0..=32 {
+        let angle = (160.0 - 10.0 * k as f64).to_radians();
+        points.push((40.0 + 20.0 * angle.cos(), 20.0 * angle.sin()));
+    }
+    Polygon::new(LineString::from(points), vec![])
+}
+
+fn length(a: Coord, b: Coord) -> f64 {
+    (b - a).x.hypot((b - a).y)
+}
+
+#[test]
+fn tip_stays_while_arc_is_resampled() {
+    for curve in [SmoothCurve::CatmullRom, SmoothCurve::BSpline] {
+        let options = SmoothOptions { curve, ..Default::default() };
+        let smoothed = smooth_polygon(&teardrop(), &options);
+        let ring = &smoothed.exterior().0;
+
+        // The tip is the only corner, the arc between its two ends is a single curve
+        assert_eq!(ring.iter().filter(|&&coord| coord == Coord { x: 0.0, y: 0.0 }).count(), 2);
+        assert!(ring.len() > 20);
+        for pair in ring.windows(2) {
+            let segment = length(pair[0], pair[1]);
+            assert!((0.8 * options.segment_length..=1.2 * options.segment_length).contains(&segment),
+                    "{:?} segment of {} instead of {}", curve, segment, options.segment_length);
+        }
+    }
+}
+
+#[test]
+fn pixel_staircase_is_left_unchanged() {
+    // Every vertex of a traced contour turns by 90 degrees, above the default corner angle
+    let staircase = Polygon::new(
+        LineString::from(vec![(0.0, 0.0), (3.0, 0.0), (3.0, 1.0), (2.0, 1.0), (2.0, 2.0), (1.0, 2.0), (1.0, 3.0), (0.0, 3.0)]),
+        vec![],
+    );
+    assert_eq!(smooth_polygon(&staircase, &SmoothOptions::default()), staircase);
+}
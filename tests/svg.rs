@@ -0,0 +1,39 @@
+use geo::{LineString, Polygon};
+use mimesis::{export_svg, fit_bezier, SvgPath};
+
+/// Square with a square hole, wound the same way as the exterior.
+fn frame() -> Polygon {
+    Polygon::new(
+        LineString::from(vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]),
+        vec![LineString::from(vec![(2.5, 2.5), (7.5, 2.5), (7.5, 1.0 / 3.0 + 7.0), (2.5, 7.5)])],
+    )
+}
+
+#[test]
+fn polygon_path_has_a_closed_subpath_per_ring() {
+    assert_eq!(frame().svg_path(), "M0,0 L10,0 L10,10 L0,10 Z M2.5,2.5 L7.5,2.5 L7.5,7.333 L2.5,7.5 Z");
+}
+
+#[test]
+fn bezier_path_has_a_closed_subpath_per_ring() {
+    let path = fit_bezier(&frame(), 0.5, 60.0).svg_path();
+    let subpaths: Vec<&str> = path.split_inclusive('Z').map(str::trim).collect();
+    assert_eq!(subpaths.len(), 2);
+    for subpath in subpaths {
+        assert!(subpath.starts_with('M') && subpath.ends_with('Z'), "{}", subpath);
+        assert!(subpath.contains(" C"), "{}", subpath);
+        assert!(!subpath.contains('L'), "{}", subpath);
+    }
+}
+
+#[test]
+fn export_fills_with_even_odd_rule() {
+    let path = std::env::temp_dir().join(format!("mimesis-{}.svg", std::process::id()));
+    export_svg(&path, 12, 10, &[frame()]).unwrap();
+    let svg = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(svg.contains(r#"viewBox="0 0 12 10""#));
+    let paths: Vec<&str> = svg.lines().filter(|line| line.trim_start().starts_with("<path")).collect();
+    assert_eq!(paths, [format!(r#"  <path fill="black" fill-rule="evenodd" d="{}"/>"#, frame().svg_path())]);
+}